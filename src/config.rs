@@ -13,6 +13,25 @@ pub struct SequenceConfig {
     /// The semester the sequence should start in
     pub starting_semester: Season,
 
-    /// The maximum number of courses that can be in a term
-    pub max_courses_per_term: u32,
+    /// The maximum number of courses that can be in a term, if terms are not
+    /// limited by credits alone
+    pub max_courses_per_term: Option<u32>,
+
+    /// The maximum number of units that can be in a term
+    pub max_credits_per_term: Option<f32>,
+
+    /// The minimum number of units in every term except the last. Terms that
+    /// can not reach it are left empty.
+    pub min_credits_per_term: Option<f32>,
+
+    /// The maximum number of terms the sequence may span
+    pub max_terms: Option<u32>,
+}
+
+impl SequenceConfig {
+    /// Whether a term with `courses` courses stays within the course limit
+    pub(crate) fn allows_courses(&self, courses: usize) -> bool {
+        self.max_courses_per_term
+            .is_none_or(|max| courses <= max as usize)
+    }
 }
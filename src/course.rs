@@ -1,14 +1,21 @@
 use core::fmt;
-use lalrpop_util::lalrpop_mod;
-use parser::PrerequisiteParser;
+use lalrpop_util::{lalrpop_mod, ParseError};
+use parser::{CourseListParser, PrerequisiteParser, RequisitesParser};
 use serde::{
     ser::{SerializeStruct, Serializer},
     Deserialize, Serialize,
 };
-use std::collections::HashMap;
+use std::{collections::HashMap, str::FromStr};
 
-use super::{prerequisite_tree::PrerequisiteTree, term::Season};
-lalrpop_mod!(pub(crate) parser);
+use super::{
+    error::{PrerequisiteParseError, SequenceError},
+    prerequisite_tree::{CourseNode, LogicNode, PrerequisiteTree},
+    term::Season,
+};
+lalrpop_mod!(#[allow(clippy::all)] pub(crate) parser);
+
+/// The number of units a course is worth when none are given
+pub const DEFAULT_CREDITS: f32 = 3.0;
 
 // A model mapping the CSV row inputs
 #[derive(Deserialize, Debug, Clone)]
@@ -18,27 +25,88 @@ pub struct CourseInput {
     catalog: u32,
     name: String,
     prerequisites: Option<String>,
-    #[serde(deserialize_with = "deserialize_bool")]
-    winter: bool,
-    #[serde(deserialize_with = "deserialize_bool")]
-    summer: bool,
-    #[serde(deserialize_with = "deserialize_bool")]
-    fall: bool,
+    #[serde(default)]
+    corequisites: Option<String>,
+    #[serde(default)]
+    antirequisites: Option<String>,
+    #[serde(default)]
+    equivalents: Option<String>,
+    #[serde(default)]
+    credits: Option<f32>,
+    /// The seasons the course is offered in, e.g. "Fall, Winter" or "F/W/S"
+    #[serde(default)]
+    terms: Option<String>,
+    #[serde(default)]
+    winter: Option<String>,
+    #[serde(default)]
+    summer: Option<String>,
+    #[serde(default)]
+    fall: Option<String>,
 }
 
-fn deserialize_bool<'de, D>(deserializer: D) -> Result<bool, D::Error>
-where
-    D: serde::Deserializer<'de>,
-{
-    let s: String = Deserialize::deserialize(deserializer)?;
-    match s.to_lowercase().as_str() {
-        "true" => Ok(true),
-        "false" => Ok(false),
-        _ => Err(serde::de::Error::custom("expected true or false")),
+/// Reads whether a course is offered from one of the season columns
+fn parse_offered(column: &str, value: &str) -> Result<bool, SequenceError> {
+    match value.to_lowercase().as_str() {
+        "true" | "yes" | "y" | "1" | "x" => Ok(true),
+        "false" | "no" | "n" | "0" => Ok(false),
+        _ => Err(SequenceError::CsvRow {
+            row: None,
+            column: Some(column.to_string()),
+            value: Some(value.to_string()),
+            message: "expected true or false".to_string(),
+        }),
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+/// Reads the seasons listed in a combined offered terms column
+fn parse_terms(value: &str) -> Result<Vec<Season>, SequenceError> {
+    value
+        .split(|c: char| c == ',' || c == '/' || c == ';' || c == '&' || c.is_whitespace())
+        .filter(|term| !term.is_empty() && !term.eq_ignore_ascii_case("and"))
+        .map(|term| match term.to_lowercase().as_str() {
+            "f" | "fall" | "autumn" => Ok(Season::Fall),
+            "w" | "winter" => Ok(Season::Winter),
+            "s" | "summer" | "spring" => Ok(Season::Summer),
+            _ => Err(SequenceError::CsvRow {
+                row: None,
+                column: Some("Terms".to_string()),
+                value: Some(value.to_string()),
+                message: format!("unknown term \"{}\"", term),
+            }),
+        })
+        .collect()
+}
+
+/// A requisite given either as catalog text or as an already parsed tree
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum RequisiteInput {
+    Text(String),
+    Tree(PrerequisiteTree),
+}
+
+// A model mapping the entries of a JSON catalog
+#[derive(Deserialize, Debug, Clone)]
+pub struct CourseJson {
+    pub(crate) subject_code: String,
+    pub(crate) catalog_code: u32,
+    #[serde(alias = "course_name")]
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) prerequisites: Option<RequisiteInput>,
+    #[serde(default)]
+    pub(crate) corequisites: Option<RequisiteInput>,
+    #[serde(default)]
+    pub(crate) antirequisites: Vec<CourseNode>,
+    #[serde(default)]
+    pub(crate) equivalents: Vec<CourseNode>,
+    #[serde(default)]
+    pub(crate) credits: Option<f32>,
+    pub(crate) terms_offered: Vec<Season>,
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+#[serde(try_from = "CourseJson")]
 pub struct Course {
     /// The subject code of the course
     pub subject_code: String,
@@ -50,38 +118,303 @@ pub struct Course {
     pub catalog_code: u32,
 
     /// The prerequisite tree for the course
-    pub(crate) prerequisites: Option<PrerequisiteTree>,
+    pub prerequisites: Option<PrerequisiteTree>,
+
+    /// The courses that must be taken before or in the same term as the course
+    pub corequisites: Option<PrerequisiteTree>,
+
+    /// The courses that can not be taken along with this course
+    pub antirequisites: Vec<CourseNode>,
+
+    /// The antirequisites this course can stand in for when checking prerequisites.
+    /// Equivalence goes one way: a course listed here only stands in for this
+    /// course if it lists this course in turn.
+    pub equivalents: Vec<CourseNode>,
+
+    /// Requisite text that could not be parsed and was ignored in lenient mode
+    pub unparsed_prerequisites: Vec<String>,
+
+    /// The number of units the course is worth
+    pub credits: f32,
 
     /// The terms during which the course is offered
     pub terms_offered: HashMap<Season, bool>,
 }
 
 impl Course {
-    pub fn new(input: CourseInput) -> Result<Self, String> {
-        let prerequisites = input
-            .prerequisites
-            .as_ref()
-            .map(|prerequisites_str| PrerequisiteParser::new().parse(prerequisites_str).unwrap());
+    pub fn new(input: CourseInput) -> Result<Self, SequenceError> {
+        Self::from_input(input, false)
+    }
+
+    /// Converts a CSV row, failing on requisite text that can not be parsed unless
+    /// `lenient`, in which case the text is kept in `unparsed_prerequisites` and
+    /// the requisite is ignored
+    pub fn from_input(input: CourseInput, lenient: bool) -> Result<Self, SequenceError> {
+        let terms_offered = input.terms_offered()?;
+
+        let mut unparsed_prerequisites = vec![];
+        let mut recover = |text: &str, err: SequenceError| {
+            if lenient {
+                unparsed_prerequisites.push(text.to_string());
+                Ok(())
+            } else {
+                Err(err)
+            }
+        };
+
+        // The prerequisite text may also list corequisites, e.g. "CSI 2110. Corequisite: CSI 2132."
+        let (prerequisites, mut corequisites) = match &input.prerequisites {
+            Some(prerequisites_str) => match RequisitesParser::new().parse(prerequisites_str) {
+                Ok(requisites) => requisites,
+                Err(err) => {
+                    recover(prerequisites_str, input.parse_error(prerequisites_str, err))?;
+                    (None, None)
+                }
+            },
+            None => (None, None),
+        };
+
+        if let Some(corequisites_str) = &input.corequisites {
+            match PrerequisiteParser::new().parse(corequisites_str) {
+                Ok(listed) => corequisites = combine_requisites(corequisites, Some(listed)),
+                Err(err) => recover(corequisites_str, input.parse_error(corequisites_str, err))?,
+            }
+        }
+
+        let mut course_list = |text: &Option<String>| match input.parse_course_list(text) {
+            Ok(courses) => Ok(courses),
+            Err(err) => recover(text.as_deref().unwrap_or_default(), err).map(|_| vec![]),
+        };
+        let antirequisites = course_list(&input.antirequisites)?;
+        let equivalents = course_list(&input.equivalents)?;
 
         Ok(Self {
             subject_code: input.subject,
             name: input.name,
             catalog_code: input.catalog,
             prerequisites,
-            terms_offered: HashMap::from([
-                (Season::Winter, input.winter),
-                (Season::Summer, input.summer),
-                (Season::Fall, input.fall),
-            ]),
+            corequisites,
+            antirequisites,
+            equivalents,
+            unparsed_prerequisites,
+            credits: input.credits.unwrap_or(DEFAULT_CREDITS),
+            terms_offered,
         })
     }
 
+    /// Converts an entry of a JSON catalog, parsing requisites given as text
+    pub fn from_json(input: CourseJson) -> Result<Self, SequenceError> {
+        let info = CourseInfo {
+            subject_code: input.subject_code.clone(),
+            name: input.name.clone(),
+            catalog_code: input.catalog_code,
+        };
+
+        let (prerequisites, from_text) = match input.prerequisites {
+            Some(RequisiteInput::Text(text)) => RequisitesParser::new()
+                .parse(&text)
+                .map_err(|err| parse_error(&info, &text, err))?,
+            Some(RequisiteInput::Tree(tree)) => (Some(tree), None),
+            None => (None, None),
+        };
+
+        let listed = match input.corequisites {
+            Some(RequisiteInput::Text(text)) => Some(
+                PrerequisiteParser::new()
+                    .parse(&text)
+                    .map_err(|err| parse_error(&info, &text, err))?,
+            ),
+            Some(RequisiteInput::Tree(tree)) => Some(tree),
+            None => None,
+        };
+
+        Ok(Self {
+            subject_code: input.subject_code,
+            name: input.name,
+            catalog_code: input.catalog_code,
+            prerequisites,
+            corequisites: combine_requisites(from_text, listed),
+            antirequisites: input.antirequisites,
+            equivalents: input.equivalents,
+            unparsed_prerequisites: vec![],
+            credits: input.credits.unwrap_or(DEFAULT_CREDITS),
+            terms_offered: [Season::Winter, Season::Summer, Season::Fall]
+                .into_iter()
+                .map(|season| (season, input.terms_offered.contains(&season)))
+                .collect(),
+        })
+    }
+
+    /// Whether this course is the one a prerequisite refers to
+    pub(crate) fn matches(&self, node: &CourseNode) -> bool {
+        self.subject_code == node.subject_code && self.catalog_code == node.catalog_code
+    }
+
+    /// Whether this course can stand in for the one a prerequisite refers to,
+    /// going by this course's equivalents alone
+    pub(crate) fn satisfies(&self, node: &CourseNode) -> bool {
+        self.matches(node) || self.equivalents.contains(node)
+    }
+
+    /// Whether this course and `other` can not both be taken
+    pub(crate) fn conflicts_with(&self, other: &Course) -> bool {
+        self.antirequisites
+            .iter()
+            .chain(self.equivalents.iter())
+            .any(|node| other.matches(node))
+            || other
+                .antirequisites
+                .iter()
+                .chain(other.equivalents.iter())
+                .any(|node| self.matches(node))
+    }
+
     pub fn info(&self) -> CourseInfo {
         CourseInfo {
             subject_code: self.subject_code.clone(),
             name: self.name.clone(),
-            catalog_code: self.catalog_code.clone(),
+            catalog_code: self.catalog_code,
+        }
+    }
+}
+
+impl CourseInput {
+    /// Combines the offered terms column with the season columns, failing when a
+    /// season column disagrees with the offered terms column
+    fn terms_offered(&self) -> Result<HashMap<Season, bool>, SequenceError> {
+        if self.terms.is_none()
+            && self.winter.is_none()
+            && self.summer.is_none()
+            && self.fall.is_none()
+        {
+            return Err(SequenceError::CsvRow {
+                row: None,
+                column: None,
+                value: None,
+                message: "expected a Terms column or Winter, Summer and Fall columns".to_string(),
+            });
+        }
+
+        let listed = match &self.terms {
+            Some(terms) => Some(parse_terms(terms)?),
+            None => None,
+        };
+
+        [
+            (Season::Winter, "Winter", &self.winter),
+            (Season::Summer, "Summer", &self.summer),
+            (Season::Fall, "Fall", &self.fall),
+        ]
+        .into_iter()
+        .map(|(season, column, value)| {
+            let in_terms = listed.as_ref().map(|listed| listed.contains(&season));
+            let offered = match (value, in_terms) {
+                (Some(value), Some(in_terms)) => {
+                    let in_column = parse_offered(column, value)?;
+                    if in_column != in_terms {
+                        return Err(SequenceError::CsvRow {
+                            row: None,
+                            column: Some(column.to_string()),
+                            value: Some(value.to_string()),
+                            message: format!(
+                                "disagrees with the Terms column \"{}\"",
+                                self.terms.as_deref().unwrap_or_default()
+                            ),
+                        });
+                    }
+                    in_column
+                }
+                (Some(value), None) => parse_offered(column, value)?,
+                (None, in_terms) => in_terms.unwrap_or(false),
+            };
+            Ok((season, offered))
+        })
+        .collect()
+    }
+
+    fn parse_course_list(&self, text: &Option<String>) -> Result<Vec<CourseNode>, SequenceError> {
+        match text {
+            Some(text) => CourseListParser::new()
+                .parse(text)
+                .map_err(|err| self.parse_error(text, err)),
+            None => Ok(vec![]),
+        }
+    }
+
+    fn parse_error<T: fmt::Display, E: fmt::Display>(
+        &self,
+        text: &str,
+        err: ParseError<usize, T, E>,
+    ) -> SequenceError {
+        let course = CourseInfo {
+            subject_code: self.subject.clone(),
+            name: self.name.clone(),
+            catalog_code: self.catalog,
+        };
+
+        parse_error(&course, text, err)
+    }
+}
+
+impl TryFrom<CourseJson> for Course {
+    type Error = SequenceError;
+
+    fn try_from(input: CourseJson) -> Result<Self, Self::Error> {
+        Course::from_json(input)
+    }
+}
+
+/// Requires both sets of requisites to be met when both are given
+fn combine_requisites(
+    first: Option<PrerequisiteTree>,
+    second: Option<PrerequisiteTree>,
+) -> Option<PrerequisiteTree> {
+    match (first, second) {
+        (Some(first), Some(second)) => {
+            Some(PrerequisiteTree::AndNode(LogicNode::new(first, second)))
+        }
+        (first, second) => first.or(second),
+    }
+}
+
+fn parse_error<T: fmt::Display, E: fmt::Display>(
+    course: &CourseInfo,
+    text: &str,
+    err: ParseError<usize, T, E>,
+) -> SequenceError {
+    SequenceError::PrerequisiteParse(Box::new(PrerequisiteParseError {
+        row: None,
+        course: course.clone(),
+        text: text.to_string(),
+        offset: error_offset(&err),
+        expected: expected_tokens(&err),
+        message: err.to_string(),
+    }))
+}
+
+/// The tokens the parser would have accepted where it failed
+fn expected_tokens<T, E>(err: &ParseError<usize, T, E>) -> Vec<String> {
+    match err {
+        ParseError::UnrecognizedToken { expected, .. }
+        | ParseError::UnrecognizedEof { expected, .. } => expected.clone(),
+        _ => vec![],
+    }
+}
+
+/// The byte offset at which a parse error occurred, if it has one
+fn error_offset<T, E>(err: &ParseError<usize, T, E>) -> Option<usize> {
+    match err {
+        ParseError::InvalidToken { location } | ParseError::UnrecognizedEof { location, .. } => {
+            Some(*location)
         }
+        ParseError::UnrecognizedToken {
+            token: (start, _, _),
+            ..
+        }
+        | ParseError::ExtraToken {
+            token: (start, _, _),
+        } => Some(*start),
+        ParseError::User { .. } => None,
     }
 }
 
@@ -90,10 +423,22 @@ impl Serialize for Course {
     where
         S: Serializer,
     {
-        let mut state = serializer.serialize_struct("Course", 3)?;
+        let mut state = serializer.serialize_struct("Course", 9)?;
         state.serialize_field("subject_code", &self.subject_code)?;
         state.serialize_field("catalog_code", &self.catalog_code)?;
         state.serialize_field("course_name", &self.name)?;
+        state.serialize_field("credits", &self.credits)?;
+        state.serialize_field("prerequisites", &self.prerequisites)?;
+        state.serialize_field("corequisites", &self.corequisites)?;
+        state.serialize_field("unparsed_prerequisites", &self.unparsed_prerequisites)?;
+        state.serialize_field(
+            "prerequisites_text",
+            &self.prerequisites.as_ref().map(PrerequisiteTree::to_string),
+        )?;
+        state.serialize_field(
+            "corequisites_text",
+            &self.corequisites.as_ref().map(PrerequisiteTree::to_string),
+        )?;
 
         state.end()
     }
@@ -111,7 +456,7 @@ impl fmt::Display for Course {
 }
 
 /// A simplified course model
-#[derive(Serialize)]
+#[derive(Debug, Serialize, PartialEq, Clone)]
 pub struct CourseInfo {
     pub subject_code: String,
     pub name: String,
@@ -128,13 +473,138 @@ impl From<Course> for CourseInfo {
     }
 }
 
+/// A course finished before sequencing, which may be a transfer credit that
+/// is not in the catalog
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct CompletedCourse {
+    pub subject_code: String,
+    pub catalog_code: u32,
+
+    /// The units of a transfer credit, ignored for courses in the catalog
+    pub credits: Option<f32>,
+}
+
+impl CompletedCourse {
+    /// Whether this is the given catalog course
+    pub(crate) fn is(&self, course: &Course) -> bool {
+        self.subject_code == course.subject_code && self.catalog_code == course.catalog_code
+    }
+
+    /// A stand-in course for a transfer credit, which is never offered
+    pub(crate) fn transfer_credit(&self) -> Course {
+        Course {
+            subject_code: self.subject_code.clone(),
+            name: String::from("Transfer credit"),
+            catalog_code: self.catalog_code,
+            prerequisites: None,
+            corequisites: None,
+            antirequisites: vec![],
+            equivalents: vec![],
+            unparsed_prerequisites: vec![],
+            credits: self.credits.unwrap_or(DEFAULT_CREDITS),
+            terms_offered: HashMap::new(),
+        }
+    }
+}
+
+impl FromStr for CompletedCourse {
+    type Err = SequenceError;
+
+    /// Parses a course code such as "MAT 1341"
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        let invalid = || SequenceError::InvalidCourseCode {
+            code: code.to_string(),
+        };
+
+        let code_start = code
+            .find(|c: char| c.is_ascii_digit())
+            .ok_or_else(invalid)?;
+        let subject = code[..code_start].trim();
+        if subject.is_empty() || !subject.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(invalid());
+        }
+
+        Ok(CompletedCourse {
+            subject_code: subject.to_uppercase(),
+            catalog_code: code[code_start..].trim().parse().map_err(|_| invalid())?,
+            credits: None,
+        })
+    }
+}
+
+/// Parses a list of course codes separated by commas, semicolons or new lines
+pub fn parse_completed_courses(codes: &str) -> Result<Vec<CompletedCourse>, SequenceError> {
+    codes
+        .split([',', ';', '\n'])
+        .map(str::trim)
+        .filter(|code| !code.is_empty())
+        .map(CompletedCourse::from_str)
+        .collect()
+}
+
+#[cfg(test)]
+impl Course {
+    /// A 3 unit course without requisites offered in the fall and winter, for
+    /// tests to adjust with the `with_*` methods
+    pub(crate) fn test(subject_code: &str, catalog_code: u32) -> Self {
+        Course {
+            subject_code: subject_code.to_string(),
+            name: String::from("A course"),
+            catalog_code,
+            prerequisites: None,
+            corequisites: None,
+            antirequisites: vec![],
+            equivalents: vec![],
+            unparsed_prerequisites: vec![],
+            credits: DEFAULT_CREDITS,
+            terms_offered: HashMap::new(),
+        }
+        .offered_in(&[Season::Fall, Season::Winter])
+    }
+
+    pub(crate) fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    pub(crate) fn with_prerequisites(mut self, prerequisites: PrerequisiteTree) -> Self {
+        self.prerequisites = Some(prerequisites);
+        self
+    }
+
+    pub(crate) fn with_corequisites(mut self, corequisites: PrerequisiteTree) -> Self {
+        self.corequisites = Some(corequisites);
+        self
+    }
+
+    pub(crate) fn with_equivalents(mut self, equivalents: Vec<CourseNode>) -> Self {
+        self.equivalents = equivalents;
+        self
+    }
+
+    pub(crate) fn with_credits(mut self, credits: f32) -> Self {
+        self.credits = credits;
+        self
+    }
+
+    /// Offers the course in exactly the given seasons
+    pub(crate) fn offered_in(mut self, seasons: &[Season]) -> Self {
+        self.terms_offered = [Season::Winter, Season::Summer, Season::Fall]
+            .into_iter()
+            .map(|season| (season, seasons.contains(&season)))
+            .collect();
+        self
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
     use std::collections::HashMap;
 
     use crate::{
-        course::{Course, CourseInput},
+        course::{parse_completed_courses, CompletedCourse, Course, CourseInput},
+        error::SequenceError,
         prerequisite_tree::{CourseNode, LogicNode, PrerequisiteTree},
         term::Season,
     };
@@ -146,9 +616,14 @@ mod tests {
             name: String::from("Intro to computing"),
             catalog: 1111,
             prerequisites: Some(String::from("CSI 2110, CSI 2132.")),
-            summer: true,
-            fall: true,
-            winter: true,
+            corequisites: None,
+            antirequisites: None,
+            equivalents: None,
+            credits: Some(1.5),
+            terms: None,
+            summer: Some(String::from("true")),
+            fall: Some(String::from("true")),
+            winter: Some(String::from("true")),
         };
 
         let expected = Course {
@@ -165,6 +640,11 @@ mod tests {
                     subject_code: String::from("CSI"),
                 }),
             ))),
+            corequisites: None,
+            antirequisites: vec![],
+            equivalents: vec![],
+            unparsed_prerequisites: vec![],
+            credits: 1.5,
             terms_offered: HashMap::from([
                 (Season::Winter, true),
                 (Season::Summer, true),
@@ -182,9 +662,14 @@ mod tests {
             name: String::from("Intro to computing"),
             catalog: 1111,
             prerequisites: None,
-            summer: true,
-            fall: true,
-            winter: true,
+            corequisites: None,
+            antirequisites: None,
+            equivalents: None,
+            credits: None,
+            terms: None,
+            summer: Some(String::from("true")),
+            fall: Some(String::from("true")),
+            winter: Some(String::from("true")),
         };
 
         let expected = Course {
@@ -192,6 +677,11 @@ mod tests {
             name: String::from("Intro to computing"),
             catalog_code: 1111,
             prerequisites: None,
+            corequisites: None,
+            antirequisites: vec![],
+            equivalents: vec![],
+            unparsed_prerequisites: vec![],
+            credits: 3.0,
             terms_offered: HashMap::from([
                 (Season::Winter, true),
                 (Season::Summer, true),
@@ -201,4 +691,228 @@ mod tests {
 
         assert_eq!(Course::new(input).unwrap(), expected);
     }
+
+    #[test]
+    fn convert_input_with_invalid_prerequisites() {
+        let input = CourseInput {
+            subject: String::from("CSI"),
+            name: String::from("Intro to computing"),
+            catalog: 1111,
+            prerequisites: Some(String::from("CSI 2110; CSI 2132.")),
+            corequisites: None,
+            antirequisites: None,
+            equivalents: None,
+            credits: None,
+            terms: None,
+            summer: Some(String::from("true")),
+            fall: Some(String::from("true")),
+            winter: Some(String::from("true")),
+        };
+
+        match Course::new(input) {
+            Err(SequenceError::PrerequisiteParse(error)) => assert_eq!(error.offset, Some(8)),
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn convert_input_with_invalid_prerequisites_leniently() {
+        let input = CourseInput {
+            subject: String::from("CSI"),
+            name: String::from("Intro to computing"),
+            catalog: 1111,
+            prerequisites: Some(String::from("Permission of the department.")),
+            corequisites: Some(String::from("CSI 1110.")),
+            antirequisites: None,
+            equivalents: None,
+            credits: None,
+            terms: None,
+            summer: Some(String::from("true")),
+            fall: Some(String::from("true")),
+            winter: Some(String::from("true")),
+        };
+
+        let course = Course::from_input(input, true).unwrap();
+
+        assert_eq!(course.prerequisites, None);
+        assert!(course.corequisites.is_some());
+        assert_eq!(
+            course.unparsed_prerequisites,
+            vec![String::from("Permission of the department.")]
+        );
+    }
+
+    #[test]
+    fn convert_input_with_corequisites() {
+        let input = CourseInput {
+            subject: String::from("PHY"),
+            name: String::from("Physics lab"),
+            catalog: 1322,
+            prerequisites: Some(String::from("MAT 1341. Corequisite: PHY 1321.")),
+            corequisites: Some(String::from("MAT 1322.")),
+            antirequisites: None,
+            equivalents: None,
+            credits: None,
+            terms: None,
+            summer: Some(String::from("true")),
+            fall: Some(String::from("true")),
+            winter: Some(String::from("true")),
+        };
+
+        let course = Course::new(input).unwrap();
+
+        assert_eq!(
+            course.prerequisites,
+            Some(PrerequisiteTree::CourseNode(CourseNode {
+                subject_code: String::from("MAT"),
+                catalog_code: 1341,
+            }))
+        );
+        assert_eq!(
+            course.corequisites,
+            Some(PrerequisiteTree::AndNode(LogicNode::new(
+                PrerequisiteTree::CourseNode(CourseNode {
+                    subject_code: String::from("PHY"),
+                    catalog_code: 1321,
+                }),
+                PrerequisiteTree::CourseNode(CourseNode {
+                    subject_code: String::from("MAT"),
+                    catalog_code: 1322,
+                }),
+            )))
+        );
+    }
+
+    #[test]
+    fn parse_completed_course_codes() {
+        assert_eq!(
+            parse_completed_courses("MAT 1341, csi1100;\n").unwrap(),
+            vec![
+                CompletedCourse {
+                    subject_code: String::from("MAT"),
+                    catalog_code: 1341,
+                    credits: None,
+                },
+                CompletedCourse {
+                    subject_code: String::from("CSI"),
+                    catalog_code: 1100,
+                    credits: None,
+                },
+            ]
+        );
+        assert_eq!(
+            parse_completed_courses("MAT 1341, 1100"),
+            Err(SequenceError::InvalidCourseCode {
+                code: String::from("1100")
+            })
+        );
+    }
+
+    #[test]
+    fn serializes_prerequisites_as_canonical_text() {
+        let input = CourseInput {
+            subject: String::from("CSI"),
+            name: String::from("Intro to computing"),
+            catalog: 1111,
+            prerequisites: Some(String::from("MAT1341, (MAT2371 or MAT 2377).")),
+            corequisites: None,
+            antirequisites: None,
+            equivalents: None,
+            credits: None,
+            terms: None,
+            summer: Some(String::from("true")),
+            fall: Some(String::from("true")),
+            winter: Some(String::from("true")),
+        };
+
+        let value = serde_json::to_value(Course::new(input).unwrap()).unwrap();
+
+        assert_eq!(
+            value["prerequisites_text"],
+            "MAT 1341, (MAT 2371 or MAT 2377)."
+        );
+        assert_eq!(value["corequisites_text"], serde_json::Value::Null);
+        assert_eq!(value["prerequisites"]["type"], "and");
+        assert_eq!(value["prerequisites"]["right"]["type"], "or");
+    }
+
+    #[test]
+    fn convert_input_with_offered_terms() {
+        let input = CourseInput {
+            subject: String::from("CSI"),
+            name: String::from("Intro to computing"),
+            catalog: 1111,
+            prerequisites: None,
+            corequisites: None,
+            antirequisites: None,
+            equivalents: None,
+            credits: None,
+            terms: Some(String::from("F/W")),
+            summer: Some(String::from("N")),
+            fall: None,
+            winter: Some(String::from("yes")),
+        };
+
+        assert_eq!(
+            Course::new(input.clone()).unwrap().terms_offered,
+            HashMap::from([
+                (Season::Winter, true),
+                (Season::Summer, false),
+                (Season::Fall, true),
+            ])
+        );
+
+        let conflicting = CourseInput {
+            winter: Some(String::from("no")),
+            ..input.clone()
+        };
+
+        match Course::new(conflicting) {
+            Err(SequenceError::CsvRow {
+                column, message, ..
+            }) => {
+                assert_eq!(column.as_deref(), Some("Winter"));
+                assert!(message.contains("Terms"));
+            }
+            other => panic!("expected a CSV row error, got {:?}", other),
+        }
+
+        let input = CourseInput {
+            terms: Some(String::from("Fall and Spring/Summer")),
+            summer: None,
+            winter: None,
+            ..input
+        };
+
+        assert_eq!(
+            Course::new(input.clone()).unwrap().terms_offered,
+            HashMap::from([
+                (Season::Winter, false),
+                (Season::Summer, true),
+                (Season::Fall, true),
+            ])
+        );
+
+        let input = CourseInput {
+            terms: Some(String::from("Fall, Sometimes")),
+            ..input
+        };
+
+        assert!(matches!(
+            Course::new(input),
+            Err(SequenceError::CsvRow { column: Some(column), .. }) if column == "Terms"
+        ));
+    }
+
+    #[test]
+    fn equivalence_goes_one_way() {
+        let older = Course::test("CSI", 1100);
+        let newer = Course::test("CSI", 1101).with_equivalents(vec![CourseNode::new("CSI", 1100)]);
+
+        assert!(newer.satisfies(&CourseNode::new("CSI", 1100)));
+        assert!(!older.satisfies(&CourseNode::new("CSI", 1101)));
+        // Either can still not be taken along with the other
+        assert!(older.conflicts_with(&newer));
+        assert!(newer.conflicts_with(&older));
+    }
 }
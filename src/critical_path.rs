@@ -0,0 +1,275 @@
+use serde::Serialize;
+
+use super::{
+    course::{Course, CourseInfo},
+    prerequisite_tree::PrerequisiteTree,
+};
+
+/// How much of the catalog depends on a course being taken
+#[derive(Debug, Serialize, PartialEq, Eq, Clone, Copy)]
+pub struct CoursePriority {
+    /// The number of terms in the longest chain of courses starting at this course
+    pub chain_length: usize,
+
+    /// The number of courses that directly or indirectly require this course
+    pub dependents: usize,
+}
+
+/// A course along with its priority
+#[derive(Debug, Serialize)]
+pub struct CriticalPathEntry {
+    pub course: CourseInfo,
+    pub priority: CoursePriority,
+}
+
+/// Computes the priority of every course, in the same order as the courses given
+pub fn course_priorities(courses: &[Course]) -> Vec<CoursePriority> {
+    let prerequisites = prerequisite_indices(courses, false);
+    let chain_lengths = chain_lengths(&prerequisites);
+    let dependents = dependent_counts(&prerequisites);
+
+    chain_lengths
+        .into_iter()
+        .zip(dependents)
+        .map(|(chain_length, dependents)| CoursePriority {
+            chain_length,
+            dependents,
+        })
+        .collect()
+}
+
+/// Lists the courses from most to least critical
+pub fn critical_path(courses: &[Course]) -> Vec<CriticalPathEntry> {
+    let mut entries: Vec<CriticalPathEntry> = courses
+        .iter()
+        .zip(course_priorities(courses))
+        .map(|(course, priority)| CriticalPathEntry {
+            course: course.info(),
+            priority,
+        })
+        .collect();
+
+    entries.sort_by_key(|entry| priority_key(&entry.priority, entry.course.catalog_code));
+    entries
+}
+
+/// A sort key ordering critical courses first, then by year
+pub(crate) fn priority_key(
+    priority: &CoursePriority,
+    catalog_code: u32,
+) -> (std::cmp::Reverse<usize>, std::cmp::Reverse<usize>, u32) {
+    (
+        std::cmp::Reverse(priority.chain_length),
+        std::cmp::Reverse(priority.dependents),
+        catalog_code,
+    )
+}
+
+/// For every course, the indices of the courses its prerequisites refer to.
+/// With `required_only`, courses that only appear as one of several
+/// alternatives are left out, otherwise corequisites are included as well.
+pub(crate) fn prerequisite_indices(courses: &[Course], required_only: bool) -> Vec<Vec<usize>> {
+    courses
+        .iter()
+        .map(|course| {
+            let mut indices = tree_indices(&course.prerequisites, courses, required_only);
+            if !required_only {
+                indices.extend(tree_indices(&course.corequisites, courses, false));
+            }
+            indices
+        })
+        .collect()
+}
+
+/// For every course, the indices of the courses named by its prerequisites,
+/// including every alternative
+pub(crate) fn prerequisite_references(courses: &[Course]) -> Vec<Vec<usize>> {
+    courses
+        .iter()
+        .map(|course| tree_indices(&course.prerequisites, courses, false))
+        .collect()
+}
+
+/// For every course, the indices of the courses its corequisites require
+pub(crate) fn corequisite_indices(courses: &[Course]) -> Vec<Vec<usize>> {
+    courses
+        .iter()
+        .map(|course| required_indices(&course.corequisites, courses))
+        .collect()
+}
+
+/// The indices of the courses a tree requires regardless of which
+/// alternatives are chosen
+pub(crate) fn required_indices(tree: &Option<PrerequisiteTree>, courses: &[Course]) -> Vec<usize> {
+    tree_indices(tree, courses, true)
+}
+
+fn tree_indices(
+    tree: &Option<PrerequisiteTree>,
+    courses: &[Course],
+    required_only: bool,
+) -> Vec<usize> {
+    tree.as_ref()
+        .map(|tree| referenced_courses(tree, courses, required_only))
+        .unwrap_or_default()
+}
+
+/// Computes the longest chain of courses starting at every course
+pub(crate) fn chain_lengths(prerequisites: &[Vec<usize>]) -> Vec<usize> {
+    let mut lengths = vec![1; prerequisites.len()];
+
+    // Chains are capped at the number of courses so prerequisite cycles terminate
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (dependent, required) in prerequisites.iter().enumerate() {
+            for &prerequisite in required {
+                let length = lengths[dependent] + 1;
+                if length > lengths[prerequisite] && length <= prerequisites.len() {
+                    lengths[prerequisite] = length;
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    lengths
+}
+
+/// Counts the courses that directly or indirectly require every course
+fn dependent_counts(prerequisites: &[Vec<usize>]) -> Vec<usize> {
+    let mut dependents: Vec<Vec<usize>> = vec![vec![]; prerequisites.len()];
+    for (dependent, required) in prerequisites.iter().enumerate() {
+        for &prerequisite in required {
+            dependents[prerequisite].push(dependent);
+        }
+    }
+
+    (0..prerequisites.len())
+        .map(|start| {
+            let mut seen = vec![false; prerequisites.len()];
+            let mut stack = vec![start];
+            while let Some(current) = stack.pop() {
+                for &dependent in &dependents[current] {
+                    if !seen[dependent] {
+                        seen[dependent] = true;
+                        stack.push(dependent);
+                    }
+                }
+            }
+
+            seen.iter()
+                .enumerate()
+                .filter(|&(i, s)| *s && i != start)
+                .count()
+        })
+        .collect()
+}
+
+fn referenced_courses(
+    tree: &PrerequisiteTree,
+    courses: &[Course],
+    required_only: bool,
+) -> Vec<usize> {
+    match tree {
+        PrerequisiteTree::CourseNode(node) => courses
+            .iter()
+            .position(|c| {
+                c.subject_code == node.subject_code && c.catalog_code == node.catalog_code
+            })
+            .into_iter()
+            .collect(),
+        PrerequisiteTree::AndNode(node) => {
+            let mut referenced = referenced_courses(&node.left, courses, required_only);
+            referenced.extend(referenced_courses(&node.right, courses, required_only));
+            referenced
+        }
+        PrerequisiteTree::OrNode(node) => {
+            let left = referenced_courses(&node.left, courses, required_only);
+            let right = referenced_courses(&node.right, courses, required_only);
+            if required_only {
+                left.into_iter().filter(|c| right.contains(c)).collect()
+            } else {
+                left.into_iter().chain(right).collect()
+            }
+        }
+        PrerequisiteTree::MinCreditNode(_) => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prerequisite_tree::LogicNode;
+
+    use super::*;
+
+    fn node(catalog_code: u32) -> PrerequisiteTree {
+        PrerequisiteTree::course("CSI", catalog_code)
+    }
+
+    #[test]
+    fn computes_chain_lengths_and_dependents() {
+        let courses = vec![
+            Course::test("CSI", 1000),
+            Course::test("CSI", 2000).with_prerequisites(node(1000)),
+            Course::test("CSI", 2001).with_prerequisites(node(1000)),
+            Course::test("CSI", 3000).with_prerequisites(node(2000)),
+            Course::test("CSI", 1001),
+        ];
+
+        let priorities = course_priorities(&courses);
+
+        assert_eq!(
+            priorities[0],
+            CoursePriority {
+                chain_length: 3,
+                dependents: 3
+            }
+        );
+        assert_eq!(
+            priorities[1],
+            CoursePriority {
+                chain_length: 2,
+                dependents: 1
+            }
+        );
+        assert_eq!(
+            priorities[4],
+            CoursePriority {
+                chain_length: 1,
+                dependents: 0
+            }
+        );
+    }
+
+    #[test]
+    fn required_only_skips_alternatives() {
+        let courses = vec![
+            Course::test("CSI", 1000),
+            Course::test("CSI", 1001),
+            Course::test("CSI", 2000).with_prerequisites(PrerequisiteTree::OrNode(LogicNode::new(
+                node(1000),
+                node(1001),
+            ))),
+        ];
+
+        assert_eq!(prerequisite_indices(&courses, false)[2], vec![0, 1]);
+        assert!(prerequisite_indices(&courses, true)[2].is_empty());
+    }
+
+    #[test]
+    fn critical_path_orders_long_chains_first() {
+        let courses = vec![
+            Course::test("CSI", 1000),
+            Course::test("CSI", 1500),
+            Course::test("CSI", 2000).with_prerequisites(node(1500)),
+        ];
+
+        let order: Vec<u32> = critical_path(&courses)
+            .iter()
+            .map(|entry| entry.course.catalog_code)
+            .collect();
+
+        assert_eq!(order, vec![1500, 1000, 2000]);
+    }
+}
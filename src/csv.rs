@@ -1,49 +1,369 @@
-use csv::{ReaderBuilder, Trim};
-use std::error::Error;
+use csv::{ErrorKind, ReaderBuilder, StringRecord, Trim, WriterBuilder};
+use serde::Serialize;
+use std::{collections::HashMap, str::FromStr};
 
-use super::course::{Course, CourseInput};
+use super::{
+    course::{Course, CourseInput},
+    error::SequenceError,
+    term::{Season, Term},
+};
 
-pub fn parse_csv_to_courses(input: &str) -> Result<Vec<Course>, Box<dyn Error>> {
+/// Options controlling how a course CSV is read
+#[derive(Debug, Default, Clone)]
+pub struct CsvOptions {
+    /// Keep courses whose requisites can not be parsed, ignoring those requisites
+    pub lenient: bool,
+
+    /// Headers to read as course fields, on top of the built-in aliases
+    pub columns: ColumnMapping,
+}
+
+/// The course fields a CSV column can hold
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum CourseField {
+    Subject,
+    Catalog,
+    Name,
+    Prerequisites,
+    Corequisites,
+    Antirequisites,
+    Equivalents,
+    Credits,
+    Terms,
+    Winter,
+    Summer,
+    Fall,
+}
+
+impl CourseField {
+    const ALL: [CourseField; 12] = [
+        CourseField::Subject,
+        CourseField::Catalog,
+        CourseField::Name,
+        CourseField::Prerequisites,
+        CourseField::Corequisites,
+        CourseField::Antirequisites,
+        CourseField::Equivalents,
+        CourseField::Credits,
+        CourseField::Terms,
+        CourseField::Winter,
+        CourseField::Summer,
+        CourseField::Fall,
+    ];
+
+    /// The header the field is read from
+    pub fn header(&self) -> &'static str {
+        match self {
+            CourseField::Subject => "Subject",
+            CourseField::Catalog => "Catalog",
+            CourseField::Name => "Name",
+            CourseField::Prerequisites => "Prerequisites",
+            CourseField::Corequisites => "Corequisites",
+            CourseField::Antirequisites => "Antirequisites",
+            CourseField::Equivalents => "Equivalents",
+            CourseField::Credits => "Credits",
+            CourseField::Terms => "Terms",
+            CourseField::Winter => "Winter",
+            CourseField::Summer => "Summer",
+            CourseField::Fall => "Fall",
+        }
+    }
+
+    /// Other headers commonly used for the field in registrar exports
+    fn aliases(&self) -> &'static [&'static str] {
+        match self {
+            CourseField::Subject => &["Subject Code", "Subj"],
+            CourseField::Catalog => &["Course Number", "Catalog Number", "Catalog Code"],
+            CourseField::Name => &["Title", "Course Title", "Course Name"],
+            CourseField::Prerequisites => &["Prerequisite", "Prereqs", "Requisites"],
+            CourseField::Corequisites => &["Corequisite", "Coreqs"],
+            CourseField::Antirequisites => &["Antirequisite", "Antireqs"],
+            CourseField::Equivalents => &["Equivalent", "Equivalencies"],
+            CourseField::Credits => &["Units", "Credit", "Credit Hours"],
+            CourseField::Terms => &["Offered Terms", "Terms Offered"],
+            CourseField::Winter => &["Winter Term"],
+            CourseField::Summer => &["Summer Term", "Spring/Summer"],
+            CourseField::Fall => &["Fall Term", "Autumn"],
+        }
+    }
+
+    /// The field a header refers to through its name or a built-in alias
+    fn from_header(header: &str) -> Option<Self> {
+        let header = normalise_header(header);
+        CourseField::ALL.into_iter().find(|field| {
+            normalise_header(field.header()) == header
+                || field
+                    .aliases()
+                    .iter()
+                    .any(|alias| normalise_header(alias) == header)
+        })
+    }
+}
+
+impl FromStr for CourseField {
+    type Err = SequenceError;
+
+    fn from_str(field: &str) -> Result<Self, Self::Err> {
+        CourseField::from_header(field).ok_or_else(|| SequenceError::InvalidConfig {
+            message: format!("unknown course field \"{}\"", field.trim()),
+        })
+    }
+}
+
+/// Compares headers regardless of case, spacing and punctuation
+fn normalise_header(header: &str) -> String {
+    header
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Maps the headers of a CSV file to the course fields they hold
+#[derive(Debug, Default, Clone)]
+pub struct ColumnMapping {
+    columns: HashMap<String, CourseField>,
+}
+
+impl ColumnMapping {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads the column with the given header as `field`
+    pub fn with_column(mut self, header: &str, field: CourseField) -> Self {
+        self.columns.insert(normalise_header(header), field);
+        self
+    }
+
+    /// Parses mappings such as "Course Number=Catalog; Title=Name", separated by
+    /// semicolons or new lines
+    pub fn parse(mappings: &str) -> Result<Self, SequenceError> {
+        mappings
+            .split([';', '\n'])
+            .map(str::trim)
+            .filter(|mapping| !mapping.is_empty())
+            .try_fold(Self::new(), |mapping, pair| {
+                let (header, field) =
+                    pair.split_once('=')
+                        .ok_or_else(|| SequenceError::InvalidConfig {
+                            message: format!(
+                                "expected a column mapping like \"Title=Name\", got \"{}\"",
+                                pair
+                            ),
+                        })?;
+                Ok(mapping.with_column(header, field.parse()?))
+            })
+    }
+
+    /// The header the course fields are read from for a column of the CSV
+    fn header_for(&self, header: &str) -> String {
+        self.columns
+            .get(&normalise_header(header))
+            .copied()
+            .or_else(|| CourseField::from_header(header))
+            .map_or_else(|| header.to_string(), |field| field.header().to_string())
+    }
+}
+
+pub fn parse_csv_to_courses(input: &str) -> Result<Vec<Course>, SequenceError> {
+    parse_csv_to_courses_with_options(input, &CsvOptions::default())
+}
+
+/// Fails with an error for every row that can not be read, if there are any
+pub fn parse_csv_to_courses_with_options(
+    input: &str,
+    options: &CsvOptions,
+) -> Result<Vec<Course>, SequenceError> {
+    let import = import_csv(input, options);
+
+    if import.errors.is_empty() {
+        Ok(import.courses)
+    } else {
+        Err(SequenceError::InvalidCsv {
+            errors: import.errors,
+        })
+    }
+}
+
+/// The courses read from a CSV file, along with an error for each row that
+/// could not be read
+#[derive(Debug, Default)]
+pub struct CsvImport {
+    pub courses: Vec<Course>,
+    pub errors: Vec<SequenceError>,
+}
+
+/// Reads every row of a CSV file, keeping the valid courses and the errors
+/// for the rest
+pub fn import_csv(input: &str, options: &CsvOptions) -> CsvImport {
     let mut rdr = ReaderBuilder::new()
         .trim(Trim::All)
         .from_reader(input.as_bytes());
-    let mut output: Vec<Course> = vec![];
-    for result in rdr.deserialize() {
-        let course_input: CourseInput = result.expect("Failed to deserialize");
+    let mut import = CsvImport::default();
+
+    let headers = match rdr.headers() {
+        Ok(headers) => headers.clone(),
+        Err(err) => {
+            import.errors.push(err.into());
+            return import;
+        }
+    };
+    let fields: StringRecord = headers
+        .iter()
+        .map(|header| options.columns.header_for(header))
+        .collect();
+
+    // Two columns read as the same field would fail every row, so fail once here
+    if let Some(err) = duplicate_field(&headers, &fields) {
+        import.errors.push(err);
+        return import;
+    }
+
+    for (index, result) in rdr.records().enumerate() {
+        let row = index as u64 + 1;
+        let course = result
+            .map_err(|err| row_error(row, &headers, None, err))
+            .and_then(|record| {
+                record
+                    .deserialize::<CourseInput>(Some(&fields))
+                    .map_err(|err| row_error(row, &headers, Some(&record), err))
+            })
+            .and_then(|course_input| {
+                Course::from_input(course_input, options.lenient)
+                    .map_err(|err| original_column(err.at_row(row), &headers, &fields))
+            });
+
+        match course {
+            Ok(course) => import.courses.push(course),
+            Err(err) => import.errors.push(err),
+        }
+    }
 
-        output.push(Course::new(course_input).unwrap())
+    import
+}
+
+/// The headers of an exported sequence, in the order of [`SequenceRow`]'s fields
+const SEQUENCE_HEADERS: [&str; 7] = [
+    "Term", "Season", "Year", "Subject", "Catalog", "Name", "Credits",
+];
+
+// A model mapping the rows of an exported sequence
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct SequenceRow<'a> {
+    /// The 1-based position of the term in the sequence
+    term: usize,
+    season: Season,
+    year: u32,
+    subject: &'a str,
+    catalog: u32,
+    name: &'a str,
+    credits: f32,
+}
+
+/// Writes a sequence as CSV, with a row for each course
+pub fn sequence_to_csv(terms: &[Term]) -> Result<String, SequenceError> {
+    // Serde only writes headers along with the first row, so an empty
+    // sequence would have none
+    let mut writer = WriterBuilder::new().has_headers(false).from_writer(vec![]);
+    writer.write_record(SEQUENCE_HEADERS)?;
+
+    for (index, term) in terms.iter().enumerate() {
+        for course in &term.courses {
+            writer.serialize(SequenceRow {
+                term: index + 1,
+                season: term.season,
+                year: term.year,
+                subject: &course.subject_code,
+                catalog: course.catalog_code,
+                name: &course.name,
+                credits: course.credits,
+            })?;
+        }
     }
 
-    Ok(output)
+    let bytes = writer
+        .into_inner()
+        .map_err(|err| csv::Error::from(err.into_error()))?;
+
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// An error for the first column read as a course field an earlier column is
+/// already read as
+fn duplicate_field(headers: &StringRecord, fields: &StringRecord) -> Option<SequenceError> {
+    fields.iter().enumerate().find_map(|(index, field)| {
+        let is_course_field = CourseField::ALL.iter().any(|f| f.header() == field);
+        let earlier = fields.iter().take(index).position(|other| other == field)?;
+
+        is_course_field.then(|| SequenceError::CsvRow {
+            row: None,
+            column: Some(headers[index].to_string()),
+            value: None,
+            message: format!(
+                "columns \"{}\" and \"{}\" are both read as the {} field",
+                &headers[earlier], &headers[index], field
+            ),
+        })
+    })
+}
+
+/// Points an error about a course field at the header it was read from
+fn original_column(
+    mut err: SequenceError,
+    headers: &StringRecord,
+    fields: &StringRecord,
+) -> SequenceError {
+    if let SequenceError::CsvRow {
+        column: Some(column),
+        ..
+    } = &mut err
+    {
+        if let Some(index) = fields.iter().position(|field| field == column) {
+            *column = headers[index].to_string();
+        }
+    }
+    err
+}
+
+/// Describes a row that could not be read, pointing at the offending value
+/// when there is one
+fn row_error(
+    row: u64,
+    headers: &StringRecord,
+    record: Option<&StringRecord>,
+    err: csv::Error,
+) -> SequenceError {
+    let (field, message) = match err.kind() {
+        ErrorKind::Deserialize { err, .. } => (err.field(), err.kind().to_string()),
+        _ => (None, err.to_string()),
+    };
+    let field = field.map(|field| field as usize);
+
+    SequenceError::CsvRow {
+        row: Some(row),
+        column: field.and_then(|field| headers.get(field)).map(String::from),
+        value: field
+            .and_then(|field| record.and_then(|record| record.get(field)))
+            .map(String::from),
+        message,
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
-    use crate::{
-        prerequisite_tree::{CourseNode, PrerequisiteTree},
-        term::Season,
-    };
+    use crate::{prerequisite_tree::PrerequisiteTree, term::Season};
 
     use super::*;
-    use std::collections::HashMap;
 
     #[test]
     fn parse_csv_test() {
-        let expected = vec![Course {
-            subject_code: "CSI".to_string(),
-            name: String::from("A computing course"),
-            catalog_code: 1111,
-            prerequisites: Some(PrerequisiteTree::CourseNode(CourseNode {
-                subject_code: "CSI".to_string(),
-                catalog_code: 1112,
-            })),
-            terms_offered: HashMap::from([
-                (Season::Winter, true),
-                (Season::Summer, true),
-                (Season::Fall, false),
-            ]),
-        }];
+        let expected = vec![Course::test("CSI", 1111)
+            .with_name("A computing course")
+            .with_prerequisites(PrerequisiteTree::course("CSI", 1112))
+            .offered_in(&[Season::Winter, Season::Summer])];
 
         let csv = "Subject,Catalog,Name,Prerequisites,Winter,Summer,Fall\nCSI,1111,A computing course,CSI 1112.,true,true,false";
 
@@ -51,4 +371,199 @@ mod tests {
 
         assert_eq!(result.unwrap(), expected);
     }
+
+    #[test]
+    fn parse_csv_invalid_row_test() {
+        let csv = "Subject,Catalog,Name,Prerequisites,Winter,Summer,Fall\nCSI,1111,A computing course,,true,maybe,false";
+
+        let result = parse_csv_to_courses(csv);
+
+        match result {
+            Err(SequenceError::InvalidCsv { errors }) => assert_eq!(
+                errors,
+                vec![SequenceError::CsvRow {
+                    row: Some(1),
+                    column: Some("Summer".to_string()),
+                    value: Some("maybe".to_string()),
+                    message: "expected true or false".to_string(),
+                }]
+            ),
+            other => panic!("expected an invalid CSV error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn import_csv_collects_row_errors_test() {
+        let csv = "Subject,Catalog,Name,Prerequisites,Winter,Summer,Fall\nCSI,1111,A course,,true,true,false\nCSI,11x2,A course,,true,true,false\nCSI,1113,A course,,true\nCSI,1114,A course,CSI 1111.,true,false,false";
+
+        let import = import_csv(csv, &CsvOptions::default());
+
+        assert_eq!(import.courses.len(), 2);
+        assert_eq!(import.courses[1].catalog_code, 1114);
+        assert_eq!(import.errors.len(), 2);
+        assert!(matches!(
+            &import.errors[0],
+            SequenceError::CsvRow { row: Some(2), column: Some(column), value: Some(value), .. }
+                if column == "Catalog" && value == "11x2"
+        ));
+        assert!(matches!(
+            &import.errors[1],
+            SequenceError::CsvRow {
+                row: Some(3),
+                column: None,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn parse_csv_credits_test() {
+        let csv = "Subject,Catalog,Name,Prerequisites,Units,Winter,Summer,Fall\nCSI,1111,A lab,,1.5,true,true,false\nCSI,1112,A course,,,true,true,false";
+
+        let result = parse_csv_to_courses(csv).unwrap();
+
+        assert_eq!(result[0].credits, 1.5);
+        assert_eq!(result[1].credits, 3.0);
+    }
+
+    #[test]
+    fn parse_csv_invalid_prerequisites_test() {
+        let csv = "Subject,Catalog,Name,Prerequisites,Winter,Summer,Fall\nCSI,1111,A course,,true,true,false\nCSI,1112,A course,CSI 1111 with permission.,true,true,false";
+
+        let errors = match parse_csv_to_courses(csv) {
+            Err(SequenceError::InvalidCsv { errors }) => errors,
+            other => panic!("expected an invalid CSV error, got {:?}", other),
+        };
+
+        match &errors[..] {
+            [SequenceError::PrerequisiteParse(error)] => {
+                assert_eq!(error.row, Some(2));
+                assert_eq!(error.text, "CSI 1111 with permission.");
+                assert_eq!(error.offset, Some(9));
+                assert!(error.expected.contains(&String::from("\"or\"")));
+            }
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+
+        let result = parse_csv_to_courses_with_options(
+            csv,
+            &CsvOptions {
+                lenient: true,
+                ..CsvOptions::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(result[1].prerequisites, None);
+        assert_eq!(result[1].unparsed_prerequisites.len(), 1);
+    }
+
+    #[test]
+    fn parse_csv_antirequisites_test() {
+        let csv = "Subject,Catalog,Name,Prerequisites,Antirequisites,Equivalents,Winter,Summer,Fall\nMAT,1320,Calculus I,,MAT 1300 or MAT 1330.,MAT 1330.,true,true,false";
+
+        let result = parse_csv_to_courses(csv).unwrap();
+
+        assert_eq!(result[0].antirequisites.len(), 2);
+        assert_eq!(result[0].equivalents.len(), 1);
+    }
+
+    #[test]
+    fn parse_csv_with_column_mapping_test() {
+        let csv = "Subject Code,Course Number,Title,Prereqs,Units,Offered W,Offered S,Offered F\nCSI,1111,A course,CSI 1110.,1.5,true,maybe,false";
+        let options = CsvOptions {
+            columns: ColumnMapping::parse("Offered W=Winter; Offered S=Summer\nOffered F = fall")
+                .unwrap(),
+            ..CsvOptions::default()
+        };
+
+        match parse_csv_to_courses_with_options(csv, &options) {
+            Err(SequenceError::InvalidCsv { errors }) => assert!(matches!(
+                &errors[..],
+                [SequenceError::CsvRow { column: Some(column), .. }] if column == "Offered S"
+            )),
+            other => panic!("expected an invalid CSV error, got {:?}", other),
+        }
+
+        let result =
+            parse_csv_to_courses_with_options(&csv.replace("maybe", "TRUE"), &options).unwrap();
+
+        assert_eq!(result[0].subject_code, "CSI");
+        assert_eq!(result[0].catalog_code, 1111);
+        assert_eq!(result[0].name, "A course");
+        assert!(result[0].prerequisites.is_some());
+        assert_eq!(result[0].credits, 1.5);
+        assert!(result[0].terms_offered[&Season::Summer]);
+        assert!(!result[0].terms_offered[&Season::Fall]);
+    }
+
+    #[test]
+    fn parse_csv_duplicate_field_test() {
+        let csv = "Subject,Subj,Catalog,Name\nCSI,CSI,1111,A course\nCSI,CSI,1112,A course";
+
+        match parse_csv_to_courses(csv) {
+            Err(SequenceError::InvalidCsv { errors }) => assert_eq!(
+                errors,
+                vec![SequenceError::CsvRow {
+                    row: None,
+                    column: Some("Subj".to_string()),
+                    value: None,
+                    message: "columns \"Subject\" and \"Subj\" are both read as the Subject field"
+                        .to_string(),
+                }]
+            ),
+            other => panic!("expected an invalid CSV error, got {:?}", other),
+        }
+
+        // Headers only read as a field through a generic word are left alone
+        let csv = "Subject,Catalog,Number,Name,Term,Fall\nCSI,1111,4,A course,2024,true";
+        assert_eq!(parse_csv_to_courses(csv).unwrap()[0].catalog_code, 1111);
+    }
+
+    #[test]
+    fn parse_column_mapping_errors_test() {
+        assert!(ColumnMapping::parse("Title").is_err());
+        assert!(ColumnMapping::parse("Title=Description").is_err());
+    }
+
+    #[test]
+    fn parse_csv_offered_terms_test() {
+        let csv = "Subject,Catalog,Name,Offered Terms\nCSI,1111,A course,\"Fall, Winter\"\nCSI,1112,A course,S";
+
+        let result = parse_csv_to_courses(csv).unwrap();
+
+        assert!(result[0].terms_offered[&Season::Fall]);
+        assert!(result[0].terms_offered[&Season::Winter]);
+        assert!(!result[0].terms_offered[&Season::Summer]);
+        assert!(!result[1].terms_offered[&Season::Fall]);
+        assert!(result[1].terms_offered[&Season::Summer]);
+    }
+
+    #[test]
+    fn sequence_to_csv_test() {
+        let csv = "Subject,Catalog,Name,Credits,Terms\nCSI,1111,\"Programming, Part I\",1.5,F\nCSI,1112,A course,,W";
+        let courses = parse_csv_to_courses(csv).unwrap();
+        let terms = vec![
+            Term::new(Season::Fall, 2023, vec![courses[0].clone()]),
+            Term::new(Season::Winter, 2024, vec![courses[1].clone()]),
+        ];
+
+        assert_eq!(
+            sequence_to_csv(&terms).unwrap(),
+            "Term,Season,Year,Subject,Catalog,Name,Credits\n\
+             1,Fall,2023,CSI,1111,\"Programming, Part I\",1.5\n\
+             2,Winter,2024,CSI,1112,A course,3.0\n"
+        );
+    }
+
+    #[test]
+    fn sequence_to_csv_empty_test() {
+        let header = "Term,Season,Year,Subject,Catalog,Name,Credits\n";
+
+        assert_eq!(sequence_to_csv(&[]).unwrap(), header);
+        assert_eq!(
+            sequence_to_csv(&[Term::new(Season::Fall, 2023, vec![])]).unwrap(),
+            header
+        );
+    }
 }
@@ -0,0 +1,464 @@
+use core::fmt;
+use serde::Serialize;
+
+use super::{course::CourseInfo, term::Season};
+
+/// The ways sequencing, validation and parsing can fail
+#[derive(Debug, Serialize, PartialEq, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SequenceError {
+    /// The prerequisites of a course can not be satisfied with the courses given
+    UnsatisfiablePrerequisite {
+        course: CourseInfo,
+        /// The codes of referenced courses that are not among the courses given
+        missing: Vec<String>,
+    },
+
+    /// A course is only offered in the summer but summer terms are not included
+    SummerOnlyCourse { course: CourseInfo },
+
+    /// The corequisites of a course can not be satisfied with the courses given
+    UnsatisfiableCorequisite {
+        course: CourseInfo,
+        /// The codes of referenced courses that are not among the courses given
+        missing: Vec<String>,
+    },
+
+    /// Courses that are corequisites of each other, and so have to be taken
+    /// together, do not fit in a single term
+    CorequisiteGroupTooLarge {
+        courses: Vec<CourseInfo>,
+        max_courses_per_term: u32,
+    },
+
+    /// Courses that are corequisites of each other are never offered in the same season
+    CorequisitesNeverOfferedTogether { courses: Vec<CourseInfo> },
+
+    /// A course is not offered in any season
+    CourseNeverOffered { course: CourseInfo },
+
+    /// A course appears more than once in the courses given
+    DuplicateCourse { course: CourseInfo },
+
+    /// Two courses are antirequisites of each other and can not both be taken
+    ConflictingAntirequisites {
+        course: CourseInfo,
+        antirequisite: CourseInfo,
+    },
+
+    /// A course is worth more units than a term may hold
+    CourseExceedsMaxCredits {
+        course: CourseInfo,
+        max_credits: f32,
+    },
+
+    /// Courses require each other as prerequisites, with no alternative that
+    /// breaks the cycle
+    PrerequisiteCycle {
+        /// Each course requires the next, ending with the first course again
+        cycle: Vec<CourseInfo>,
+    },
+
+    /// Sequencing stopped making progress with courses left to schedule
+    Infeasible { stuck: Vec<StuckCourse> },
+
+    /// A term before the last could not be filled up to the minimum load, and
+    /// leaving terms empty until a later season did not help
+    BelowMinimumCredits {
+        season: Season,
+        year: u32,
+        credits: f32,
+        min_credits: f32,
+    },
+
+    /// The courses could not be scheduled within the maximum number of terms
+    MaxTermsExceeded {
+        max_terms: u32,
+        remaining: Vec<CourseInfo>,
+    },
+
+    /// The prerequisites of a course could not be parsed
+    PrerequisiteParse(Box<PrerequisiteParseError>),
+
+    /// A course code is not a subject followed by a catalog number
+    InvalidCourseCode { code: String },
+
+    /// A course is not among the courses given
+    UnknownCourse { code: String },
+
+    /// A row of a CSV file could not be read
+    CsvRow {
+        /// The 1-based record number, not counting the header
+        row: Option<u64>,
+        /// The header of the column holding the offending value
+        column: Option<String>,
+        value: Option<String>,
+        message: String,
+    },
+
+    /// A JSON catalog is not valid JSON or does not match the catalog schema
+    InvalidJson {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+
+    /// A program definition file could not be read
+    InvalidProgram {
+        /// The 1-based line of the file the problem was found on
+        line: Option<usize>,
+        column: Option<usize>,
+        message: String,
+    },
+
+    /// Rows of a CSV file could not be read, with an error for each
+    InvalidCsv { errors: Vec<SequenceError> },
+
+    /// The sequencing options are not usable
+    InvalidConfig { message: String },
+
+    /// The body of a request could not be read
+    UnreadableInput { message: String },
+}
+
+/// Where and why the prerequisites of a course could not be parsed
+#[derive(Debug, Serialize, PartialEq, Clone)]
+pub struct PrerequisiteParseError {
+    /// The 1-based CSV record the course came from, not counting the header
+    pub row: Option<u64>,
+    pub course: CourseInfo,
+    /// The prerequisite text as given
+    pub text: String,
+    /// The byte offset in `text` at which parsing failed
+    pub offset: Option<usize>,
+    /// The tokens that would have been accepted at `offset`
+    pub expected: Vec<String>,
+    pub message: String,
+}
+
+impl fmt::Display for SequenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SequenceError::UnsatisfiablePrerequisite { course, .. } => write!(
+                f,
+                "Prerequisite for course {} {} can not be satisfied with the courses given",
+                course.subject_code, course.catalog_code
+            ),
+            SequenceError::UnsatisfiableCorequisite { course, .. } => write!(
+                f,
+                "Corequisite for course {} {} can not be satisfied with the courses given",
+                course.subject_code, course.catalog_code
+            ),
+            SequenceError::CorequisiteGroupTooLarge {
+                courses,
+                max_courses_per_term,
+            } => write!(
+                f,
+                "Corequisites {} must be taken together but only {} courses fit in a term",
+                course_list(courses),
+                max_courses_per_term
+            ),
+            SequenceError::CorequisitesNeverOfferedTogether { courses } => write!(
+                f,
+                "Corequisites {} must be taken together but are never offered in the same term",
+                course_list(courses)
+            ),
+            SequenceError::SummerOnlyCourse { course } => write!(
+                f,
+                "Course {} {} can only be taken in the summer",
+                course.subject_code, course.catalog_code
+            ),
+            SequenceError::CourseNeverOffered { course } => write!(
+                f,
+                "Course {} {} is not offered in any term",
+                course.subject_code, course.catalog_code
+            ),
+            SequenceError::DuplicateCourse { course } => write!(
+                f,
+                "Course {} {} appears more than once",
+                course.subject_code, course.catalog_code
+            ),
+            SequenceError::ConflictingAntirequisites {
+                course,
+                antirequisite,
+            } => write!(
+                f,
+                "Courses {} {} and {} {} are antirequisites and can not both be taken",
+                antirequisite.subject_code,
+                antirequisite.catalog_code,
+                course.subject_code,
+                course.catalog_code
+            ),
+            SequenceError::CourseExceedsMaxCredits {
+                course,
+                max_credits,
+            } => write!(
+                f,
+                "Course {} {} is worth more than the maximum of {} units per term",
+                course.subject_code, course.catalog_code, max_credits
+            ),
+            SequenceError::PrerequisiteCycle { cycle } => {
+                write!(f, "Prerequisites form a cycle: ")?;
+                for (i, course) in cycle.iter().enumerate() {
+                    let separator = if i == 0 { "" } else { " -> " };
+                    write!(
+                        f,
+                        "{}{} {}",
+                        separator, course.subject_code, course.catalog_code
+                    )?;
+                }
+                Ok(())
+            }
+            SequenceError::BelowMinimumCredits {
+                season,
+                year,
+                credits,
+                min_credits,
+            } => write!(
+                f,
+                "Term {} {} only has {} of the minimum {} units",
+                season, year, credits, min_credits
+            ),
+            SequenceError::Infeasible { stuck } => {
+                write!(f, "Unable to schedule courses")?;
+                for (i, stuck_course) in stuck.iter().enumerate() {
+                    let separator = if i == 0 { ":" } else { "," };
+                    write!(
+                        f,
+                        "{} {} {} ({})",
+                        separator,
+                        stuck_course.course.subject_code,
+                        stuck_course.course.catalog_code,
+                        stuck_course.reason
+                    )?;
+                }
+                Ok(())
+            }
+            SequenceError::MaxTermsExceeded {
+                max_terms,
+                remaining,
+            } => write!(
+                f,
+                "Unable to schedule {} remaining course(s) within {} terms",
+                remaining.len(),
+                max_terms
+            ),
+            SequenceError::PrerequisiteParse(error) => {
+                let PrerequisiteParseError {
+                    row,
+                    course,
+                    message,
+                    ..
+                } = error.as_ref();
+                write!(
+                    f,
+                    "Unable to parse prerequisites for course {} {}",
+                    course.subject_code, course.catalog_code
+                )?;
+                if let Some(row) = row {
+                    write!(f, " on row {}", row)?;
+                }
+                write!(f, ": {}", message)
+            }
+            SequenceError::InvalidCourseCode { code } => {
+                write!(f, "Invalid course code \"{}\"", code)
+            }
+            SequenceError::UnknownCourse { code } => {
+                write!(f, "Course {} is not in the catalog", code)
+            }
+            SequenceError::CsvRow {
+                row,
+                column,
+                value,
+                message,
+            } => {
+                match row {
+                    Some(row) => write!(f, "Invalid CSV row {}", row)?,
+                    None => write!(f, "Invalid CSV")?,
+                }
+                if let Some(column) = column {
+                    write!(f, ", column {}", column)?;
+                }
+                if let Some(value) = value {
+                    write!(f, " (\"{}\")", value)?;
+                }
+                write!(f, ": {}", message)
+            }
+            SequenceError::InvalidJson {
+                line,
+                column,
+                message,
+            } => write!(
+                f,
+                "Invalid JSON at line {} column {}: {}",
+                line, column, message
+            ),
+            SequenceError::InvalidProgram {
+                line,
+                column,
+                message,
+            } => match (line, column) {
+                (Some(line), Some(column)) => write!(
+                    f,
+                    "Invalid program file at line {} column {}: {}",
+                    line, column, message
+                ),
+                (Some(line), None) => {
+                    write!(f, "Invalid program file at line {}: {}", line, message)
+                }
+                _ => write!(f, "Invalid program file: {}", message),
+            },
+            SequenceError::InvalidCsv { errors } => {
+                write!(f, "Unable to read {} CSV row(s)", errors.len())?;
+                for (i, err) in errors.iter().enumerate() {
+                    let separator = if i == 0 { ":" } else { ";" };
+                    write!(f, "{} {}", separator, err)?;
+                }
+                Ok(())
+            }
+            SequenceError::InvalidConfig { message } => write!(f, "Invalid config: {}", message),
+            SequenceError::UnreadableInput { message } => {
+                write!(f, "Unable to read the input: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SequenceError {}
+
+impl SequenceError {
+    /// Records the CSV record a course level error came from
+    pub(crate) fn at_row(mut self, record: u64) -> Self {
+        match &mut self {
+            SequenceError::PrerequisiteParse(error) => error.row = Some(record),
+            SequenceError::CsvRow { row, .. } => *row = Some(record),
+            _ => {}
+        }
+        self
+    }
+}
+
+fn course_list(courses: &[CourseInfo]) -> String {
+    courses
+        .iter()
+        .map(|course| format!("{} {}", course.subject_code, course.catalog_code))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// A course the sequencer was unable to schedule
+#[derive(Debug, Serialize, PartialEq, Clone)]
+pub struct StuckCourse {
+    pub course: CourseInfo,
+    pub reason: StuckReason,
+}
+
+/// Why a course could not be scheduled
+#[derive(Debug, Serialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum StuckReason {
+    /// The prerequisites of the course can never be met
+    PrerequisitesUnreachable,
+
+    /// The course can not be taken after a course already taken
+    AntirequisiteTaken,
+
+    /// The course and the corequisites it has to be taken with do not fit in a
+    /// term within the course and unit limits
+    ExceedsTermLimits,
+
+    /// The corequisites of the course can never be taken by or alongside it
+    CorequisitesUnreachable,
+}
+
+impl fmt::Display for StuckReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StuckReason::PrerequisitesUnreachable => write!(f, "prerequisites can never be met"),
+            StuckReason::AntirequisiteTaken => write!(f, "an antirequisite was already taken"),
+            StuckReason::ExceedsTermLimits => {
+                write!(f, "does not fit in a term with its corequisites")
+            }
+            StuckReason::CorequisitesUnreachable => write!(f, "corequisites can never be met"),
+        }
+    }
+}
+
+impl From<csv::Error> for SequenceError {
+    fn from(err: csv::Error) -> Self {
+        SequenceError::CsvRow {
+            row: err.position().map(|position| position.record()),
+            column: None,
+            value: None,
+            message: err.to_string(),
+        }
+    }
+}
+
+impl From<std::io::Error> for SequenceError {
+    fn from(err: std::io::Error) -> Self {
+        SequenceError::UnreadableInput {
+            message: err.to_string(),
+        }
+    }
+}
+
+impl From<serde_json::Error> for SequenceError {
+    fn from(err: serde_json::Error) -> Self {
+        SequenceError::InvalidJson {
+            line: err.line(),
+            column: err.column(),
+            message: err.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_with_kind_tag() {
+        let err = SequenceError::SummerOnlyCourse {
+            course: CourseInfo {
+                subject_code: "CSI".to_string(),
+                name: "A computing course".to_string(),
+                catalog_code: 1100,
+            },
+        };
+
+        assert_eq!(
+            serde_json::to_value(&err).unwrap(),
+            serde_json::json!({
+                "kind": "summer_only_course",
+                "course": {
+                    "subject_code": "CSI",
+                    "name": "A computing course",
+                    "catalog_code": 1100,
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn serializes_boxed_parse_errors_flat() {
+        let err = SequenceError::PrerequisiteParse(Box::new(PrerequisiteParseError {
+            row: Some(2),
+            course: CourseInfo {
+                subject_code: "CSI".to_string(),
+                name: "A computing course".to_string(),
+                catalog_code: 2110,
+            },
+            text: "CSI 1101;".to_string(),
+            offset: Some(8),
+            expected: vec![],
+            message: "Invalid token at 8".to_string(),
+        }));
+
+        let value = serde_json::to_value(&err).unwrap();
+
+        assert_eq!(value["kind"], "prerequisite_parse");
+        assert_eq!(value["row"], 2);
+        assert_eq!(value["offset"], 8);
+    }
+}
@@ -0,0 +1,446 @@
+use std::collections::BTreeMap;
+use std::mem::discriminant;
+
+use serde::Serialize;
+
+use super::{
+    course::Course,
+    prerequisite_tree::{MinCreditNode, PrerequisiteTree},
+    term::Term,
+};
+
+/// A node of a course dependency graph
+#[derive(Debug, Serialize, PartialEq, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GraphNode {
+    Course {
+        subject_code: String,
+        catalog_code: u32,
+        /// Whether the course is in the catalog, rather than only named in a requisite
+        in_catalog: bool,
+        /// The index of the term the course is taken in, if the graph has terms
+        term: Option<usize>,
+    },
+    /// All of the nodes leading into it are required
+    And,
+    /// Any one of the nodes leading into it is required
+    Or,
+    /// A minimum number of units, described as in the catalog
+    MinCredits { description: String },
+}
+
+/// Whether an edge leads to a prerequisite or a corequisite
+#[derive(Debug, Serialize, PartialEq, Eq, Copy, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeKind {
+    Prerequisite,
+    Corequisite,
+}
+
+/// An edge from a requirement to the node that requires it
+#[derive(Debug, Serialize, PartialEq, Eq, Copy, Clone)]
+pub struct GraphEdge {
+    pub from: usize,
+    pub to: usize,
+    pub kind: EdgeKind,
+}
+
+/// How to group the courses of a graph when exporting it
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum Clustering {
+    #[default]
+    None,
+    /// By the level of the catalog code, e.g. 2000
+    Level,
+    /// By the term the course is taken in, see [`CourseGraph::with_terms`]
+    Term,
+}
+
+/// The courses of a catalog and the requisites between them, with a junction
+/// node for each AND or OR and a node for each minimum number of units
+#[derive(Debug, Serialize, Default)]
+pub struct CourseGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+    /// The terms courses can be clustered by, e.g. "Fall 2024"
+    pub terms: Vec<String>,
+}
+
+impl CourseGraph {
+    pub fn new(courses: &[Course]) -> Self {
+        let mut graph = Self::default();
+
+        for course in courses {
+            graph.course_node(&course.subject_code, course.catalog_code, true);
+        }
+        for course in courses {
+            let course_index = graph.course_node(&course.subject_code, course.catalog_code, true);
+            let requisites = [
+                (&course.prerequisites, EdgeKind::Prerequisite),
+                (&course.corequisites, EdgeKind::Corequisite),
+            ];
+            for (tree, kind) in requisites {
+                if let Some(tree) = tree {
+                    let from = graph.add_tree(tree, kind);
+                    graph.edges.push(GraphEdge {
+                        from,
+                        to: course_index,
+                        kind,
+                    });
+                }
+            }
+        }
+
+        graph
+    }
+
+    /// Records the term of a generated sequence each course is taken in
+    pub fn with_terms(mut self, terms: &[Term]) -> Self {
+        self.terms = terms
+            .iter()
+            .map(|term| format!("{} {}", term.season, term.year))
+            .collect();
+
+        for node in &mut self.nodes {
+            if let GraphNode::Course {
+                subject_code,
+                catalog_code,
+                term,
+                ..
+            } = node
+            {
+                *term = terms.iter().position(|t| {
+                    t.courses.iter().any(|course| {
+                        course.subject_code == *subject_code && course.catalog_code == *catalog_code
+                    })
+                });
+            }
+        }
+
+        self
+    }
+
+    /// Exports the graph in the Graphviz DOT language
+    pub fn to_dot(&self, clustering: Clustering) -> String {
+        let mut out = String::from("digraph prerequisites {\n    rankdir=LR;\n");
+        let (clusters, rest) = self.clusters(clustering);
+
+        for (index, (label, nodes)) in clusters.iter().enumerate() {
+            out += &format!(
+                "    subgraph cluster_{} {{\n        label=\"{}\";\n",
+                index,
+                escape_dot(label)
+            );
+            for &node in nodes {
+                out += &format!("        {}\n", self.dot_node(node));
+            }
+            out += "    }\n";
+        }
+        for node in rest {
+            out += &format!("    {}\n", self.dot_node(node));
+        }
+        for edge in &self.edges {
+            out += &format!("    n{} -> n{}", edge.from, edge.to);
+            if edge.kind == EdgeKind::Corequisite {
+                out += " [style=dashed]";
+            }
+            out += ";\n";
+        }
+
+        out + "}\n"
+    }
+
+    /// Exports the graph as a Mermaid flowchart
+    pub fn to_mermaid(&self, clustering: Clustering) -> String {
+        let mut out = String::from("flowchart LR\n");
+        let (clusters, rest) = self.clusters(clustering);
+
+        for (index, (label, nodes)) in clusters.iter().enumerate() {
+            out += &format!(
+                "    subgraph cluster_{}[\"{}\"]\n",
+                index,
+                escape_mermaid(label)
+            );
+            for &node in nodes {
+                out += &format!("        {}\n", self.mermaid_node(node));
+            }
+            out += "    end\n";
+        }
+        for node in rest {
+            out += &format!("    {}\n", self.mermaid_node(node));
+        }
+        for edge in &self.edges {
+            let arrow = match edge.kind {
+                EdgeKind::Prerequisite => "-->",
+                EdgeKind::Corequisite => "-.->",
+            };
+            out += &format!("    n{} {} n{}\n", edge.from, arrow, edge.to);
+        }
+
+        let external = self.nodes.iter().any(|node| {
+            matches!(
+                node,
+                GraphNode::Course {
+                    in_catalog: false,
+                    ..
+                }
+            )
+        });
+        if external {
+            out += "    classDef external stroke-dasharray: 5 5\n";
+        }
+
+        out
+    }
+
+    /// The index of the node of a course, adding it if it is not in the graph yet
+    fn course_node(&mut self, subject: &str, catalog: u32, in_catalog: bool) -> usize {
+        let existing = self.nodes.iter().position(|node| {
+            matches!(node, GraphNode::Course { subject_code, catalog_code, .. }
+                if subject_code == subject && *catalog_code == catalog)
+        });
+
+        existing.unwrap_or_else(|| {
+            self.nodes.push(GraphNode::Course {
+                subject_code: subject.to_string(),
+                catalog_code: catalog,
+                in_catalog,
+                term: None,
+            });
+            self.nodes.len() - 1
+        })
+    }
+
+    /// Adds the nodes of a requisite tree, returning the index of its root
+    fn add_tree(&mut self, tree: &PrerequisiteTree, kind: EdgeKind) -> usize {
+        let junction = match tree {
+            PrerequisiteTree::CourseNode(node) => {
+                return self.course_node(&node.subject_code, node.catalog_code, false)
+            }
+            PrerequisiteTree::MinCreditNode(node) => return self.min_credit_node(node),
+            PrerequisiteTree::AndNode(_) => GraphNode::And,
+            PrerequisiteTree::OrNode(_) => GraphNode::Or,
+        };
+
+        self.nodes.push(junction);
+        let index = self.nodes.len() - 1;
+        for operand in operands(tree) {
+            let from = self.add_tree(operand, kind);
+            self.edges.push(GraphEdge {
+                from,
+                to: index,
+                kind,
+            });
+        }
+
+        index
+    }
+
+    fn min_credit_node(&mut self, node: &MinCreditNode) -> usize {
+        self.nodes.push(GraphNode::MinCredits {
+            description: node.to_string(),
+        });
+        self.nodes.len() - 1
+    }
+
+    /// Groups the course nodes into labelled clusters, returning the clusters
+    /// and the nodes left outside of them
+    fn clusters(&self, clustering: Clustering) -> (Vec<(String, Vec<usize>)>, Vec<usize>) {
+        let mut levels: BTreeMap<u32, Vec<usize>> = BTreeMap::new();
+        let mut terms: Vec<Vec<usize>> = vec![vec![]; self.terms.len()];
+        let mut rest = vec![];
+
+        for (index, node) in self.nodes.iter().enumerate() {
+            match (clustering, node) {
+                (Clustering::Level, GraphNode::Course { catalog_code, .. }) => levels
+                    .entry(catalog_code / 1000 * 1000)
+                    .or_default()
+                    .push(index),
+                (
+                    Clustering::Term,
+                    GraphNode::Course {
+                        term: Some(term), ..
+                    },
+                ) => terms[*term].push(index),
+                _ => rest.push(index),
+            }
+        }
+
+        let clusters = match clustering {
+            Clustering::None => vec![],
+            Clustering::Level => levels
+                .into_iter()
+                .map(|(level, nodes)| (format!("{} level", level), nodes))
+                .collect(),
+            Clustering::Term => self
+                .terms
+                .iter()
+                .cloned()
+                .zip(terms)
+                .filter(|(_, nodes)| !nodes.is_empty())
+                .collect(),
+        };
+
+        (clusters, rest)
+    }
+
+    fn dot_node(&self, index: usize) -> String {
+        let attributes = match &self.nodes[index] {
+            GraphNode::Course {
+                subject_code,
+                catalog_code,
+                in_catalog,
+                ..
+            } => format!(
+                "label=\"{} {}\", shape=box{}",
+                escape_dot(subject_code),
+                catalog_code,
+                if *in_catalog { "" } else { ", style=dashed" }
+            ),
+            GraphNode::And => "label=\"and\", shape=circle".to_string(),
+            GraphNode::Or => "label=\"or\", shape=circle".to_string(),
+            GraphNode::MinCredits { description } => {
+                format!("label=\"{}\", shape=note", escape_dot(description))
+            }
+        };
+
+        format!("n{} [{}];", index, attributes)
+    }
+
+    fn mermaid_node(&self, index: usize) -> String {
+        match &self.nodes[index] {
+            GraphNode::Course {
+                subject_code,
+                catalog_code,
+                in_catalog,
+                ..
+            } => format!(
+                "n{}[\"{} {}\"]{}",
+                index,
+                escape_mermaid(subject_code),
+                catalog_code,
+                if *in_catalog { "" } else { ":::external" }
+            ),
+            GraphNode::And => format!("n{}((\"and\"))", index),
+            GraphNode::Or => format!("n{}((\"or\"))", index),
+            GraphNode::MinCredits { description } => {
+                format!("n{}[/\"{}\"/]", index, escape_mermaid(description))
+            }
+        }
+    }
+}
+
+/// The operands of an AND or OR, with nested nodes of the same kind flattened
+/// into a single junction
+fn operands(tree: &PrerequisiteTree) -> Vec<&PrerequisiteTree> {
+    match tree {
+        PrerequisiteTree::AndNode(node) | PrerequisiteTree::OrNode(node) => {
+            [&node.left, &node.right]
+                .into_iter()
+                .flat_map(|side| {
+                    if discriminant(side.as_ref()) == discriminant(tree) {
+                        operands(side)
+                    } else {
+                        vec![side.as_ref()]
+                    }
+                })
+                .collect()
+        }
+        _ => vec![tree],
+    }
+}
+
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn escape_mermaid(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::csv::parse_csv_to_courses;
+    use crate::term::Season;
+
+    use super::*;
+
+    fn courses() -> Vec<Course> {
+        let csv = "Subject,Catalog,Name,Prerequisites,Corequisites,Winter,Summer,Fall\n\
+                   CSI,1100,Intro,,,true,false,true\n\
+                   CSI,2110,Data structures,\"CSI 1100, (MAT 1341 or MAT 1348 or MAT 1322).\",,true,false,true\n\
+                   CSI,2101,Discrete,CSI 1100.,CSI 2110.,true,false,true";
+
+        parse_csv_to_courses(csv).unwrap()
+    }
+
+    #[test]
+    fn builds_junction_nodes() {
+        let graph = CourseGraph::new(&courses());
+
+        let ands = graph.nodes.iter().filter(|n| **n == GraphNode::And).count();
+        let ors: Vec<usize> = (0..graph.nodes.len())
+            .filter(|&i| graph.nodes[i] == GraphNode::Or)
+            .collect();
+        let externals = graph
+            .nodes
+            .iter()
+            .filter(|n| {
+                matches!(
+                    n,
+                    GraphNode::Course {
+                        in_catalog: false,
+                        ..
+                    }
+                )
+            })
+            .count();
+
+        assert_eq!(ands, 1);
+        // The three alternatives share a single OR junction
+        assert_eq!(ors.len(), 1);
+        assert_eq!(graph.edges.iter().filter(|e| e.to == ors[0]).count(), 3);
+        assert_eq!(externals, 3);
+        assert!(graph.edges.contains(&GraphEdge {
+            from: 1,
+            to: 2,
+            kind: EdgeKind::Corequisite,
+        }));
+    }
+
+    #[test]
+    fn to_dot_clusters_by_level() {
+        let dot = CourseGraph::new(&courses()).to_dot(Clustering::Level);
+
+        assert!(dot.starts_with("digraph prerequisites {\n"));
+        assert!(dot.contains("subgraph cluster_0 {\n        label=\"1000 level\";\n        n0 [label=\"CSI 1100\", shape=box];\n"));
+        assert!(dot.contains("label=\"MAT 1341\", shape=box, style=dashed"));
+        assert!(dot.contains("n1 -> n2 [style=dashed];"));
+        assert!(dot.ends_with("}\n"));
+    }
+
+    #[test]
+    fn to_mermaid_clusters_by_term() {
+        let courses = courses();
+        let terms = vec![
+            Term::new(Season::Fall, 2024, vec![courses[0].clone()]),
+            Term::new(
+                Season::Winter,
+                2025,
+                vec![courses[1].clone(), courses[2].clone()],
+            ),
+        ];
+
+        let mermaid = CourseGraph::new(&courses)
+            .with_terms(&terms)
+            .to_mermaid(Clustering::Term);
+
+        assert!(mermaid.contains(
+            "    subgraph cluster_0[\"Fall 2024\"]\n        n0[\"CSI 1100\"]\n    end\n"
+        ));
+        assert!(mermaid.contains("    subgraph cluster_1[\"Winter 2025\"]\n        n1[\"CSI 2110\"]\n        n2[\"CSI 2101\"]\n    end\n"));
+        assert!(mermaid.contains("((\"or\"))"));
+        assert!(mermaid.contains("n1 -.-> n2"));
+        assert!(mermaid.contains("classDef external"));
+    }
+}
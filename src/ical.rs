@@ -0,0 +1,422 @@
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{
+    error::SequenceError,
+    term::{Season, Term},
+};
+
+/// A day of the year, without the year
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
+pub struct MonthDay {
+    pub month: u32,
+    pub day: u32,
+}
+
+impl MonthDay {
+    pub fn new(month: u32, day: u32) -> Self {
+        Self { month, day }
+    }
+}
+
+/// The first and last days of the terms of a season
+#[derive(Debug, Serialize, Deserialize, PartialEq, Copy, Clone)]
+pub struct TermDates {
+    pub start: MonthDay,
+    pub end: MonthDay,
+}
+
+/// When the terms of each season start and end
+#[derive(Debug, Serialize, Deserialize, PartialEq, Copy, Clone)]
+#[serde(default)]
+pub struct TermCalendar {
+    pub fall: TermDates,
+    pub winter: TermDates,
+    pub summer: TermDates,
+}
+
+impl Default for TermCalendar {
+    fn default() -> Self {
+        Self {
+            fall: TermDates {
+                start: MonthDay::new(9, 1),
+                end: MonthDay::new(12, 22),
+            },
+            winter: TermDates {
+                start: MonthDay::new(1, 6),
+                end: MonthDay::new(4, 30),
+            },
+            summer: TermDates {
+                start: MonthDay::new(5, 1),
+                end: MonthDay::new(8, 31),
+            },
+        }
+    }
+}
+
+impl TermCalendar {
+    /// Parses term dates such as "Fall=09-04..12-31; Winter=01-06..04-30",
+    /// separated by semicolons or new lines. Seasons left out keep their
+    /// usual dates.
+    pub fn parse(terms: &str) -> Result<Self, SequenceError> {
+        terms
+            .split([';', '\n'])
+            .map(str::trim)
+            .filter(|term| !term.is_empty())
+            .try_fold(Self::default(), |mut calendar, term| {
+                let invalid = || SequenceError::InvalidConfig {
+                    message: format!(
+                        "expected term dates like \"Fall=09-01..12-22\", got \"{}\"",
+                        term
+                    ),
+                };
+                let (season, dates) = term.split_once('=').ok_or_else(invalid)?;
+                let (start, end) = dates.split_once("..").ok_or_else(invalid)?;
+                let dates = TermDates {
+                    start: parse_month_day(start).ok_or_else(invalid)?,
+                    end: parse_month_day(end).ok_or_else(invalid)?,
+                };
+
+                match season.trim().to_lowercase().as_str() {
+                    "fall" | "autumn" => calendar.fall = dates,
+                    "winter" => calendar.winter = dates,
+                    "summer" | "spring" => calendar.summer = dates,
+                    _ => {
+                        return Err(SequenceError::InvalidConfig {
+                            message: format!("unknown term \"{}\"", season.trim()),
+                        })
+                    }
+                }
+                Ok(calendar)
+            })
+    }
+
+    fn dates(&self, season: Season) -> TermDates {
+        match season {
+            Season::Fall => self.fall,
+            Season::Winter => self.winter,
+            Season::Summer => self.summer,
+        }
+    }
+}
+
+/// Reads a date written as `MM-DD`
+fn parse_month_day(date: &str) -> Option<MonthDay> {
+    let (month, day) = date.trim().split_once('-')?;
+
+    Some(MonthDay::new(month.parse().ok()?, day.parse().ok()?))
+}
+
+/// Writes a sequence as an iCalendar file with an all-day event spanning each
+/// term that has courses, listing them. Every event is stamped with `created`,
+/// the time the file was made.
+pub fn sequence_to_ical(
+    terms: &[Term],
+    calendar: &TermCalendar,
+    created: SystemTime,
+) -> Result<String, SequenceError> {
+    let stamp = timestamp_value(created);
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//usequence//Course Plan//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+
+    for (index, term) in terms.iter().enumerate() {
+        if term.courses.is_empty() {
+            continue;
+        }
+
+        let dates = calendar.dates(term.season);
+        // Terms that end before they start run into the next year
+        let end_year = if dates.end < dates.start {
+            term.year + 1
+        } else {
+            term.year
+        };
+        for (year, date) in [(term.year, dates.start), (end_year, dates.end)] {
+            if !is_valid_date(year, date) {
+                return Err(SequenceError::InvalidConfig {
+                    message: format!(
+                        "{} terms can not start or end on {:02}-{:02}",
+                        term.season, date.month, date.day
+                    ),
+                });
+            }
+        }
+
+        let courses: Vec<String> = term
+            .courses
+            .iter()
+            .map(|course| course.to_string())
+            .collect();
+
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!(
+                "UID:term-{}-{}-{}@usequence",
+                index + 1,
+                term.season.to_string().to_lowercase(),
+                term.year
+            ),
+            format!("DTSTAMP:{}", stamp),
+            format!("DTSTART;VALUE=DATE:{}", date_value(term.year, dates.start)),
+            // The end of an all-day event is exclusive
+            format!("DTEND;VALUE=DATE:{}", date_value_after(end_year, dates.end)),
+            format!(
+                "SUMMARY:{}",
+                escape_text(&format!(
+                    "{} {} ({} units)",
+                    term.season,
+                    term.year,
+                    term.credits()
+                ))
+            ),
+            format!("DESCRIPTION:{}", escape_text(&courses.join("\n"))),
+            "END:VEVENT".to_string(),
+        ]);
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    Ok(lines.iter().map(|line| fold_line(line) + "\r\n").collect())
+}
+
+fn is_leap_year(year: u32) -> bool {
+    (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400)
+}
+
+fn days_in_year(year: u32) -> u64 {
+    if is_leap_year(year) {
+        366
+    } else {
+        365
+    }
+}
+
+fn days_in_month(year: u32, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+fn is_valid_date(year: u32, date: MonthDay) -> bool {
+    (1..=12).contains(&date.month) && (1..=days_in_month(year, date.month)).contains(&date.day)
+}
+
+fn date_value(year: u32, date: MonthDay) -> String {
+    format!("{:04}{:02}{:02}", year, date.month, date.day)
+}
+
+/// The UTC date-time value of a point in time, counting times before 1970 as
+/// its start
+fn timestamp_value(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let mut days = seconds / 86_400;
+
+    let mut year = 1970;
+    while days >= days_in_year(year) {
+        days -= days_in_year(year);
+        year += 1;
+    }
+    let mut month = 1;
+    while days >= u64::from(days_in_month(year, month)) {
+        days -= u64::from(days_in_month(year, month));
+        month += 1;
+    }
+
+    let time_of_day = seconds % 86_400;
+    format!(
+        "{}T{:02}{:02}{:02}Z",
+        date_value(year, MonthDay::new(month, days as u32 + 1)),
+        time_of_day / 3_600,
+        time_of_day / 60 % 60,
+        time_of_day % 60
+    )
+}
+
+/// The date value of the day after `date`
+fn date_value_after(year: u32, date: MonthDay) -> String {
+    if date.day < days_in_month(year, date.month) {
+        date_value(year, MonthDay::new(date.month, date.day + 1))
+    } else if date.month < 12 {
+        date_value(year, MonthDay::new(date.month + 1, 1))
+    } else {
+        date_value(year + 1, MonthDay::new(1, 1))
+    }
+}
+
+/// Escapes the characters iCalendar gives meaning to in text values
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace(['\r', '\n'], "\\n")
+}
+
+/// Splits a content line so that no line is longer than 75 octets, continuing
+/// each with a space
+fn fold_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+
+    for c in line.chars() {
+        if length + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::course::Course;
+
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn sequence_to_ical_test() {
+        let terms = vec![
+            Term::new(
+                Season::Fall,
+                2024,
+                vec![
+                    Course::test("CSI", 1111).with_name("Programming, Part I"),
+                    Course::test("CSI", 1112).with_name("A course"),
+                ],
+            ),
+            Term::new(
+                Season::Winter,
+                2025,
+                vec![Course::test("CSI", 2110).with_name("Data structures")],
+            ),
+        ];
+        let calendar = TermCalendar {
+            fall: TermDates {
+                start: MonthDay::new(9, 4),
+                end: MonthDay::new(12, 31),
+            },
+            ..TermCalendar::default()
+        };
+
+        // 2024-09-04 15:30:00 UTC
+        let created = UNIX_EPOCH + Duration::from_secs(1_725_463_800);
+
+        let ical = sequence_to_ical(&terms, &calendar, created).unwrap();
+        let lines: Vec<&str> = ical.split("\r\n").collect();
+
+        assert_eq!(lines[0], "BEGIN:VCALENDAR");
+        assert!(lines.contains(&"DTSTART;VALUE=DATE:20240904"));
+        assert!(lines.contains(&"DTEND;VALUE=DATE:20250101"));
+        assert!(lines.contains(&"SUMMARY:Fall 2024 (6 units)"));
+        assert!(lines.contains(&"DESCRIPTION:CSI 1111: Programming\\, Part I\\nCSI 1112: A course"));
+        assert!(lines.contains(&"DTSTART;VALUE=DATE:20250106"));
+        assert!(lines.contains(&"DTEND;VALUE=DATE:20250501"));
+        assert_eq!(
+            lines
+                .iter()
+                .filter(|line| line.starts_with("DTSTAMP:"))
+                .count(),
+            2
+        );
+        assert!(lines
+            .iter()
+            .filter(|line| line.starts_with("DTSTAMP:"))
+            .all(|line| *line == "DTSTAMP:20240904T153000Z"));
+        assert_eq!(ical.matches("BEGIN:VEVENT").count(), 2);
+        assert!(ical.ends_with("END:VCALENDAR\r\n"));
+    }
+
+    #[test]
+    fn rejects_invalid_term_dates() {
+        let terms = vec![Term::new(
+            Season::Winter,
+            2025,
+            vec![Course::test("CSI", 2110)],
+        )];
+        let calendar = TermCalendar {
+            winter: TermDates {
+                start: MonthDay::new(2, 29),
+                end: MonthDay::new(4, 30),
+            },
+            ..TermCalendar::default()
+        };
+
+        assert!(sequence_to_ical(&terms, &calendar, UNIX_EPOCH).is_err());
+    }
+
+    #[test]
+    fn runs_terms_into_the_next_year() {
+        let terms = vec![
+            Term::new(Season::Winter, 2024, vec![Course::test("CSI", 2110)]),
+            Term::new(Season::Summer, 2024, vec![]),
+        ];
+        let calendar = TermCalendar {
+            winter: TermDates {
+                start: MonthDay::new(12, 15),
+                end: MonthDay::new(2, 28),
+            },
+            ..TermCalendar::default()
+        };
+
+        let ical = sequence_to_ical(&terms, &calendar, UNIX_EPOCH).unwrap();
+
+        assert!(ical.contains("DTSTART;VALUE=DATE:20241215\r\n"));
+        assert!(ical.contains("DTEND;VALUE=DATE:20250301\r\n"));
+        assert_eq!(ical.matches("BEGIN:VEVENT").count(), 1);
+    }
+
+    #[test]
+    fn escape_text_test() {
+        assert_eq!(escape_text("a\r\nb\rc\nd"), "a\\nb\\nc\\nd");
+    }
+
+    #[test]
+    fn timestamp_value_test() {
+        assert_eq!(timestamp_value(UNIX_EPOCH), "19700101T000000Z");
+        // 2024-02-29 23:59:59 UTC
+        assert_eq!(
+            timestamp_value(UNIX_EPOCH + Duration::from_secs(1_709_251_199)),
+            "20240229T235959Z"
+        );
+    }
+
+    #[test]
+    fn parse_term_calendar_test() {
+        let calendar = TermCalendar::parse("Fall=09-04..12-31;\n winter = 1-6 .. 4-30").unwrap();
+
+        assert_eq!(calendar.fall.start, MonthDay::new(9, 4));
+        assert_eq!(calendar.fall.end, MonthDay::new(12, 31));
+        assert_eq!(calendar.winter.start, MonthDay::new(1, 6));
+        assert_eq!(calendar.summer, TermCalendar::default().summer);
+
+        assert!(TermCalendar::parse("Fall=09-04").is_err());
+        assert!(TermCalendar::parse("Fall=September..December").is_err());
+        assert!(TermCalendar::parse("Spring break=03-01..03-07").is_err());
+    }
+
+    #[test]
+    fn folds_long_lines() {
+        let line = "DESCRIPTION:".to_string() + &"x".repeat(100);
+
+        let folded = fold_line(&line);
+
+        assert!(folded.split("\r\n").all(|part| part.len() <= 75));
+        assert_eq!(folded.replace("\r\n ", ""), line);
+    }
+}
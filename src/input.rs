@@ -1,14 +1,127 @@
+use std::collections::VecDeque;
+
+use serde::Serialize;
+
 use super::{
-    config::SequenceConfig, course::Course, prerequisites::validate_prerequisites, term::Season,
+    config::SequenceConfig,
+    course::{CompletedCourse, Course},
+    critical_path::{corequisite_indices, prerequisite_references, required_indices},
+    error::{SequenceError, StuckCourse, StuckReason},
+    prerequisite_tree::PrerequisiteTree,
+    prerequisites::{evaluate_prerequisite_tree, validate_prerequisites},
+    term::Season,
 };
 
-pub(crate) fn validate_input(courses: &Vec<Course>, config: &SequenceConfig) -> Result<(), String> {
-    for course in courses {
-        if !validate_prerequisites(&course.prerequisites, courses) {
-            return Err(format!(
-                "Prerequisite for course {} {} can not be satisfied with the courses given",
-                course.subject_code, course.catalog_code
-            ));
+/// Every problem found with a set of courses, in the order the courses were given
+#[derive(Debug, Serialize, PartialEq, Default)]
+pub struct ValidationReport {
+    pub issues: Vec<SequenceError>,
+}
+
+impl ValidationReport {
+    /// Whether the courses can be sequenced
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Checks every course against the config, collecting all problems found
+pub fn validation_report(courses: &[Course], config: &SequenceConfig) -> ValidationReport {
+    validation_report_with_completed(courses, &[], config)
+}
+
+/// Checks the courses left to take against the config, counting courses already
+/// completed towards their prerequisites
+pub fn validation_report_with_completed(
+    courses: &[Course],
+    completed: &[Course],
+    config: &SequenceConfig,
+) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    let available: Vec<Course> = completed.iter().chain(courses).cloned().collect();
+
+    match (config.max_courses_per_term, config.max_credits_per_term) {
+        (Some(0), _) => report.issues.push(SequenceError::InvalidConfig {
+            message: "max courses per term must be greater than 0".to_string(),
+        }),
+        (None, None) => report.issues.push(SequenceError::InvalidConfig {
+            message: "either max courses or max credits per term must be given".to_string(),
+        }),
+        _ => {}
+    }
+
+    if let (Some(min), Some(max)) = (config.min_credits_per_term, config.max_credits_per_term) {
+        if min > max {
+            report.issues.push(SequenceError::InvalidConfig {
+                message: "min credits per term can not be greater than max credits per term"
+                    .to_string(),
+            });
+        }
+    }
+
+    for (index, course) in courses.iter().enumerate() {
+        let is_duplicate = courses[..index].iter().any(|other| {
+            other.subject_code == course.subject_code && other.catalog_code == course.catalog_code
+        });
+
+        if is_duplicate {
+            report.issues.push(SequenceError::DuplicateCourse {
+                course: course.info(),
+            });
+        }
+
+        // Each conflicting pair is reported once, from its later course
+        for other in completed
+            .iter()
+            .chain(&courses[..index])
+            .filter(|other| course.conflicts_with(other))
+        {
+            report
+                .issues
+                .push(SequenceError::ConflictingAntirequisites {
+                    course: course.info(),
+                    antirequisite: other.info(),
+                });
+        }
+
+        if let Some(max_credits) = config.max_credits_per_term {
+            if course.credits > max_credits {
+                report.issues.push(SequenceError::CourseExceedsMaxCredits {
+                    course: course.info(),
+                    max_credits,
+                });
+            }
+        }
+
+        if !validate_prerequisites(&course.prerequisites, &available) {
+            report
+                .issues
+                .push(SequenceError::UnsatisfiablePrerequisite {
+                    course: course.info(),
+                    missing: course
+                        .prerequisites
+                        .as_ref()
+                        .map(|tree| missing_courses(tree, &available))
+                        .unwrap_or_default(),
+                });
+        }
+
+        if !validate_prerequisites(&course.corequisites, &available) {
+            report.issues.push(SequenceError::UnsatisfiableCorequisite {
+                course: course.info(),
+                missing: course
+                    .corequisites
+                    .as_ref()
+                    .map(|tree| missing_courses(tree, &available))
+                    .unwrap_or_default(),
+            });
+        }
+
+        if !course.terms_offered.values().any(|offered| *offered) {
+            report.issues.push(SequenceError::CourseNeverOffered {
+                course: course.info(),
+            });
+            continue;
         }
 
         let course_only_offered_in_summer = course.terms_offered.iter().all(|(season, offered)| {
@@ -20,21 +133,310 @@ pub(crate) fn validate_input(courses: &Vec<Course>, config: &SequenceConfig) ->
         });
 
         if !config.include_summer && course_only_offered_in_summer {
-            return Err(format!(
-                "Course {} {} can only be taken in the summer",
-                course.subject_code, course.catalog_code,
-            ));
+            report.issues.push(SequenceError::SummerOnlyCourse {
+                course: course.info(),
+            });
+        }
+    }
+
+    report
+        .issues
+        .extend(corequisite_group_issues(courses, config));
+    report
+        .issues
+        .extend(prerequisite_cycle_issues(courses, completed));
+
+    report
+}
+
+/// Finds courses whose prerequisites can never be met because they lead back
+/// to the course itself, reporting one cycle for each group of such courses.
+/// A cycle that an alternative prerequisite gets around is not reported
+fn prerequisite_cycle_issues(courses: &[Course], completed: &[Course]) -> Vec<SequenceError> {
+    let reachable = reachable_courses(courses, completed);
+    let blocked_by = blocked_prerequisites(courses, &reachable);
+
+    let mut in_reported_cycle = vec![false; courses.len()];
+    let mut issues = vec![];
+    for start in 0..courses.len() {
+        if reachable[start] || in_reported_cycle[start] {
+            continue;
+        }
+
+        if let Some(cycle) = shortest_cycle(start, &blocked_by) {
+            // Courses that require each other are reported once, as one cycle
+            for i in reaching(start, &blocked_by) {
+                if reaching(i, &blocked_by).contains(&start) {
+                    in_reported_cycle[i] = true;
+                }
+            }
+
+            issues.push(SequenceError::PrerequisiteCycle {
+                cycle: cycle.iter().map(|&i| courses[i].info()).collect(),
+            });
+        }
+    }
+
+    issues
+}
+
+/// Which courses can ever be taken, found by taking every course whose
+/// prerequisites can be met until no more can be
+pub(crate) fn reachable_courses(courses: &[Course], completed: &[Course]) -> Vec<bool> {
+    let mut reachable = vec![false; courses.len()];
+    let mut available = completed.to_vec();
+    loop {
+        let newly_reachable: Vec<usize> = (0..courses.len())
+            .filter(|&i| {
+                !reachable[i] && validate_prerequisites(&courses[i].prerequisites, &available)
+            })
+            .collect();
+
+        if newly_reachable.is_empty() {
+            return reachable;
+        }
+        for i in newly_reachable {
+            reachable[i] = true;
+            available.push(courses[i].clone());
+        }
+    }
+}
+
+/// The courses each course that can never be taken references that can never
+/// be taken either, since only those can hold it back
+pub(crate) fn blocked_prerequisites(courses: &[Course], reachable: &[bool]) -> Vec<Vec<usize>> {
+    prerequisite_references(courses)
+        .into_iter()
+        .enumerate()
+        .map(|(i, references)| {
+            if reachable[i] {
+                vec![]
+            } else {
+                references.into_iter().filter(|&j| !reachable[j]).collect()
+            }
+        })
+        .collect()
+}
+
+/// The indices of every course reachable from `start` along `edges`
+pub(crate) fn reaching(start: usize, edges: &[Vec<usize>]) -> Vec<usize> {
+    let mut seen = vec![false; edges.len()];
+    let mut stack = vec![start];
+    while let Some(current) = stack.pop() {
+        for &next in &edges[current] {
+            if !seen[next] {
+                seen[next] = true;
+                stack.push(next);
+            }
+        }
+    }
+
+    (0..edges.len()).filter(|&i| seen[i]).collect()
+}
+
+/// The shortest path along `edges` from `start` back to itself, if any, with
+/// `start` at both ends
+pub(crate) fn shortest_cycle(start: usize, edges: &[Vec<usize>]) -> Option<Vec<usize>> {
+    let mut previous: Vec<Option<usize>> = vec![None; edges.len()];
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(current) = queue.pop_front() {
+        for &next in &edges[current] {
+            if next == start {
+                let mut path = vec![current];
+                while let Some(before) = previous[*path.last().unwrap()] {
+                    path.push(before);
+                }
+                path.reverse();
+                path.push(start);
+                return Some(path);
+            }
+            if previous[next].is_none() && next != start {
+                previous[next] = Some(current);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
+
+/// Finds courses that are corequisites of each other, and so have to share a
+/// term, that can never be placed together
+fn corequisite_group_issues(courses: &[Course], config: &SequenceConfig) -> Vec<SequenceError> {
+    let corequisites = corequisite_indices(courses);
+    let reachable: Vec<Vec<bool>> = (0..courses.len())
+        .map(|start| {
+            let mut seen = vec![false; courses.len()];
+            let mut stack = vec![start];
+            while let Some(current) = stack.pop() {
+                for &next in &corequisites[current] {
+                    if !seen[next] {
+                        seen[next] = true;
+                        stack.push(next);
+                    }
+                }
+            }
+            seen
+        })
+        .collect();
+
+    let mut issues = vec![];
+    for (first, reachable_from_first) in reachable.iter().enumerate() {
+        let group: Vec<usize> = (0..courses.len())
+            .filter(|&other| reachable_from_first[other] && reachable[other][first])
+            .collect();
+
+        // Each group is reported once, from its first course
+        if group.len() < 2 || group[0] != first {
+            continue;
+        }
+
+        let group_info = group.iter().map(|&i| courses[i].info()).collect();
+
+        if let Some(max_courses_per_term) = config.max_courses_per_term {
+            if group.len() > max_courses_per_term as usize {
+                issues.push(SequenceError::CorequisiteGroupTooLarge {
+                    courses: group_info,
+                    max_courses_per_term,
+                });
+                continue;
+            }
+        }
+
+        let offered_together = [Season::Fall, Season::Winter, Season::Summer]
+            .iter()
+            .filter(|season| config.include_summer || **season != Season::Summer)
+            .any(|season| {
+                group
+                    .iter()
+                    .all(|&i| *courses[i].terms_offered.get(season).unwrap_or(&false))
+            });
+
+        if !offered_together {
+            issues.push(SequenceError::CorequisitesNeverOfferedTogether {
+                courses: group_info,
+            });
+        }
+    }
+
+    issues
+}
+
+/// Returns the first problem found with the courses, if any
+pub(crate) fn validate_input(
+    courses: &[Course],
+    completed: &[Course],
+    config: &SequenceConfig,
+) -> Result<(), SequenceError> {
+    match validation_report_with_completed(courses, completed, config)
+        .issues
+        .into_iter()
+        .next()
+    {
+        Some(issue) => Err(issue),
+        None => Ok(()),
+    }
+}
+
+/// Splits the catalog into the courses left to take and the courses already
+/// completed, adding stand-ins for completed courses that are not in the catalog
+pub fn split_completed(
+    courses: Vec<Course>,
+    completed: &[CompletedCourse],
+) -> (Vec<Course>, Vec<Course>) {
+    let (taken, remaining): (Vec<Course>, Vec<Course>) = courses
+        .into_iter()
+        .partition(|course| completed.iter().any(|done| done.is(course)));
+
+    let transfer_credits = completed
+        .iter()
+        .filter(|done| !taken.iter().any(|course| done.is(course)))
+        .map(CompletedCourse::transfer_credit);
+
+    let taken = taken.iter().cloned().chain(transfer_credits).collect();
+    (remaining, taken)
+}
+
+/// Explains why each of the remaining courses can not be scheduled once no more
+/// progress can be made after taking `courses_taken`
+pub(crate) fn stuck_courses(
+    remaining: &[Course],
+    courses_taken: &[Course],
+    config: &SequenceConfig,
+) -> Vec<StuckCourse> {
+    remaining
+        .iter()
+        .enumerate()
+        .map(|(index, course)| {
+            // Courses never offered are rejected before sequencing starts
+            let reason = if !validate_prerequisites(&course.prerequisites, courses_taken) {
+                StuckReason::PrerequisitesUnreachable
+            } else if courses_taken
+                .iter()
+                .any(|other| course.conflicts_with(other))
+            {
+                StuckReason::AntirequisiteTaken
+            } else if !bundle_fits(index, remaining, config) {
+                StuckReason::ExceedsTermLimits
+            } else {
+                // With everything else in order, only the corequisites it needs
+                // alongside it can hold a course back
+                StuckReason::CorequisitesUnreachable
+            };
+
+            StuckCourse {
+                course: course.info(),
+                reason,
+            }
+        })
+        .collect()
+}
+
+/// Whether a course fits in a term within the course and unit limits along
+/// with the remaining courses it needs as corequisites
+fn bundle_fits(index: usize, remaining: &[Course], config: &SequenceConfig) -> bool {
+    let mut bundle = vec![index];
+    let mut next = 0;
+    while next < bundle.len() {
+        for required in required_indices(&remaining[bundle[next]].corequisites, remaining) {
+            if !bundle.contains(&required) {
+                bundle.push(required);
+            }
         }
+        next += 1;
+    }
+
+    let credits: f32 = bundle.iter().map(|&i| remaining[i].credits).sum();
+    config.allows_courses(bundle.len())
+        && config.max_credits_per_term.is_none_or(|max| credits <= max)
+}
+
+/// The codes of the courses referenced by the unmet parts of the tree that are
+/// not in the catalog, leaving out the alternatives of requirements that are met
+pub(crate) fn missing_courses(tree: &PrerequisiteTree, courses: &[Course]) -> Vec<String> {
+    if evaluate_prerequisite_tree(tree, courses) {
+        return vec![];
     }
 
-    Ok(())
+    match tree {
+        PrerequisiteTree::CourseNode(node) => {
+            vec![format!("{} {}", node.subject_code, node.catalog_code)]
+        }
+        PrerequisiteTree::AndNode(node) | PrerequisiteTree::OrNode(node) => {
+            let mut missing = missing_courses(&node.left, courses);
+            missing.extend(missing_courses(&node.right, courses));
+            missing
+        }
+        PrerequisiteTree::MinCreditNode(_) => vec![],
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
 
-    use crate::prerequisite_tree::{CourseNode, PrerequisiteTree};
+    use crate::prerequisite_tree::{CourseNode, LogicNode, PrerequisiteTree};
 
     use super::*;
 
@@ -46,6 +448,11 @@ mod tests {
                 name: String::from("A computing course"),
                 catalog_code: 1100,
                 prerequisites: None,
+                corequisites: None,
+                antirequisites: vec![],
+                equivalents: vec![],
+                unparsed_prerequisites: vec![],
+                credits: 3.0,
                 terms_offered: HashMap::from([(Season::Fall, true)]),
             },
             Course {
@@ -56,18 +463,26 @@ mod tests {
                     subject_code: "CSI".to_string(),
                     catalog_code: 1100,
                 })),
+                corequisites: None,
+                antirequisites: vec![],
+                equivalents: vec![],
+                unparsed_prerequisites: vec![],
+                credits: 3.0,
                 terms_offered: HashMap::from([(Season::Winter, true)]),
             },
         ];
 
         let config = SequenceConfig {
             include_summer: true,
-            max_courses_per_term: 5,
+            max_courses_per_term: Some(5),
             starting_semester: Season::Fall,
             starting_year: 2023,
+            max_terms: None,
+            max_credits_per_term: None,
+            min_credits_per_term: None,
         };
 
-        let result = validate_input(&courses, &config);
+        let result = validate_input(&courses, &[], &config);
         assert!(result.is_ok());
     }
 
@@ -79,6 +494,11 @@ mod tests {
                 name: String::from("A computing course"),
                 catalog_code: 1100,
                 prerequisites: None,
+                corequisites: None,
+                antirequisites: vec![],
+                equivalents: vec![],
+                unparsed_prerequisites: vec![],
+                credits: 3.0,
                 terms_offered: HashMap::from([(Season::Fall, true)]),
             },
             Course {
@@ -89,20 +509,28 @@ mod tests {
                     subject_code: "CSI".to_string(),
                     catalog_code: 1200,
                 })),
+                corequisites: None,
+                antirequisites: vec![],
+                equivalents: vec![],
+                unparsed_prerequisites: vec![],
+                credits: 3.0,
                 terms_offered: HashMap::from([(Season::Winter, true)]),
             },
         ];
         let config = SequenceConfig {
             include_summer: true,
-            max_courses_per_term: 5,
+            max_courses_per_term: Some(5),
             starting_semester: Season::Fall,
             starting_year: 2023,
+            max_terms: None,
+            max_credits_per_term: None,
+            min_credits_per_term: None,
         };
 
-        let result = validate_input(&courses, &config);
+        let result = validate_input(&courses, &[], &config);
         assert!(result.is_err());
         assert_eq!(
-            result.unwrap_err(),
+            result.unwrap_err().to_string(),
             "Prerequisite for course CSI 1300 can not be satisfied with the courses given"
                 .to_string()
         );
@@ -116,6 +544,11 @@ mod tests {
                 name: String::from("A computing course"),
                 catalog_code: 1100,
                 prerequisites: None,
+                corequisites: None,
+                antirequisites: vec![],
+                equivalents: vec![],
+                unparsed_prerequisites: vec![],
+                credits: 3.0,
                 terms_offered: HashMap::from([(Season::Summer, true)]),
             },
             Course {
@@ -126,21 +559,428 @@ mod tests {
                     subject_code: "CSI".to_string(),
                     catalog_code: 1200,
                 })),
+                corequisites: None,
+                antirequisites: vec![],
+                equivalents: vec![],
+                unparsed_prerequisites: vec![],
+                credits: 3.0,
                 terms_offered: HashMap::from([(Season::Winter, true)]),
             },
         ];
         let config = SequenceConfig {
             include_summer: false,
-            max_courses_per_term: 5,
+            max_courses_per_term: Some(5),
             starting_semester: Season::Fall,
             starting_year: 2023,
+            max_terms: None,
+            max_credits_per_term: None,
+            min_credits_per_term: None,
         };
 
-        let result = validate_input(&courses, &config);
+        let result = validate_input(&courses, &[], &config);
         assert!(result.is_err());
         assert_eq!(
             result.unwrap_err(),
-            "Course CSI 1100 can only be taken in the summer".to_string()
+            SequenceError::SummerOnlyCourse {
+                course: courses[0].info()
+            }
+        );
+    }
+
+    #[test]
+    fn should_return_error_if_course_is_never_offered() {
+        let courses = vec![Course {
+            subject_code: "CSI".to_string(),
+            name: String::from("A computing course"),
+            catalog_code: 1100,
+            prerequisites: None,
+            corequisites: None,
+            antirequisites: vec![],
+            equivalents: vec![],
+            unparsed_prerequisites: vec![],
+            credits: 3.0,
+            terms_offered: HashMap::from([(Season::Fall, false), (Season::Winter, false)]),
+        }];
+        let config = SequenceConfig {
+            include_summer: true,
+            max_courses_per_term: Some(5),
+            starting_semester: Season::Fall,
+            starting_year: 2023,
+            max_terms: None,
+            max_credits_per_term: None,
+            min_credits_per_term: None,
+        };
+
+        let result = validate_input(&courses, &[], &config);
+        assert_eq!(
+            result.unwrap_err(),
+            SequenceError::CourseNeverOffered {
+                course: courses[0].info()
+            }
+        );
+    }
+
+    #[test]
+    fn report_should_contain_every_issue() {
+        let courses = vec![
+            Course {
+                subject_code: "CSI".to_string(),
+                name: String::from("A computing course"),
+                catalog_code: 1100,
+                prerequisites: None,
+                corequisites: None,
+                antirequisites: vec![],
+                equivalents: vec![],
+                unparsed_prerequisites: vec![],
+                credits: 3.0,
+                terms_offered: HashMap::from([(Season::Summer, true)]),
+            },
+            Course {
+                subject_code: "CSI".to_string(),
+                name: String::from("A computing course"),
+                catalog_code: 1300,
+                prerequisites: Some(PrerequisiteTree::CourseNode(CourseNode {
+                    subject_code: "CSI".to_string(),
+                    catalog_code: 1200,
+                })),
+                corequisites: None,
+                antirequisites: vec![],
+                equivalents: vec![],
+                unparsed_prerequisites: vec![],
+                credits: 3.0,
+                terms_offered: HashMap::from([(Season::Winter, false)]),
+            },
+            Course {
+                subject_code: "CSI".to_string(),
+                name: String::from("A computing course"),
+                catalog_code: 1100,
+                prerequisites: None,
+                corequisites: None,
+                antirequisites: vec![],
+                equivalents: vec![],
+                unparsed_prerequisites: vec![],
+                credits: 3.0,
+                terms_offered: HashMap::from([(Season::Fall, true)]),
+            },
+        ];
+        let config = SequenceConfig {
+            include_summer: false,
+            max_courses_per_term: Some(5),
+            starting_semester: Season::Fall,
+            starting_year: 2023,
+            max_terms: None,
+            max_credits_per_term: None,
+            min_credits_per_term: None,
+        };
+
+        let report = validation_report(&courses, &config);
+
+        assert!(!report.is_valid());
+        assert_eq!(
+            report.issues,
+            vec![
+                SequenceError::SummerOnlyCourse {
+                    course: courses[0].info()
+                },
+                SequenceError::UnsatisfiablePrerequisite {
+                    course: courses[1].info(),
+                    missing: vec!["CSI 1200".to_string()]
+                },
+                SequenceError::CourseNeverOffered {
+                    course: courses[1].info()
+                },
+                SequenceError::DuplicateCourse {
+                    course: courses[2].info()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn report_should_only_list_missing_alternatives_of_unmet_requirements() {
+        let prerequisites = PrerequisiteTree::AndNode(LogicNode::new(
+            PrerequisiteTree::OrNode(LogicNode::new(
+                PrerequisiteTree::course("CSI", 1100),
+                PrerequisiteTree::course("ITI", 1100),
+            )),
+            PrerequisiteTree::OrNode(LogicNode::new(
+                PrerequisiteTree::course("MAT", 1341),
+                PrerequisiteTree::course("MAT", 1322),
+            )),
+        ));
+        let courses = vec![
+            Course::test("CSI", 1100),
+            Course::test("CSI", 2110).with_prerequisites(prerequisites),
+        ];
+        let config = SequenceConfig {
+            include_summer: false,
+            max_courses_per_term: Some(5),
+            starting_semester: Season::Fall,
+            starting_year: 2023,
+            max_terms: None,
+            max_credits_per_term: None,
+            min_credits_per_term: None,
+        };
+
+        assert_eq!(
+            validation_report(&courses, &config).issues,
+            vec![SequenceError::UnsatisfiablePrerequisite {
+                course: courses[1].info(),
+                missing: vec!["MAT 1341".to_string(), "MAT 1322".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn report_should_flag_courses_worth_more_than_max_credits() {
+        let courses = vec![Course {
+            subject_code: "CSI".to_string(),
+            name: String::from("A capstone project"),
+            catalog_code: 4900,
+            prerequisites: None,
+            corequisites: None,
+            antirequisites: vec![],
+            equivalents: vec![],
+            unparsed_prerequisites: vec![],
+            credits: 12.0,
+            terms_offered: HashMap::from([(Season::Fall, true)]),
+        }];
+        let config = SequenceConfig {
+            include_summer: false,
+            max_courses_per_term: Some(5),
+            starting_semester: Season::Fall,
+            starting_year: 2023,
+            max_terms: None,
+            max_credits_per_term: Some(9.0),
+            min_credits_per_term: Some(12.0),
+        };
+
+        let report = validation_report(&courses, &config);
+
+        assert_eq!(
+            report.issues,
+            vec![
+                SequenceError::InvalidConfig {
+                    message: "min credits per term can not be greater than max credits per term"
+                        .to_string()
+                },
+                SequenceError::CourseExceedsMaxCredits {
+                    course: courses[0].info(),
+                    max_credits: 9.0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn report_should_flag_corequisites_that_can_not_share_a_term() {
+        let corequisite = |catalog_code| {
+            Some(PrerequisiteTree::CourseNode(CourseNode {
+                subject_code: "PHY".to_string(),
+                catalog_code,
+            }))
+        };
+        let courses = vec![
+            Course {
+                subject_code: "PHY".to_string(),
+                name: String::from("A physics course"),
+                catalog_code: 1321,
+                prerequisites: None,
+                corequisites: corequisite(1322),
+                antirequisites: vec![],
+                equivalents: vec![],
+                unparsed_prerequisites: vec![],
+                credits: 3.0,
+                terms_offered: HashMap::from([(Season::Fall, true)]),
+            },
+            Course {
+                subject_code: "PHY".to_string(),
+                name: String::from("A physics lab"),
+                catalog_code: 1322,
+                prerequisites: None,
+                corequisites: corequisite(1321),
+                antirequisites: vec![],
+                equivalents: vec![],
+                unparsed_prerequisites: vec![],
+                credits: 1.5,
+                terms_offered: HashMap::from([(Season::Winter, true)]),
+            },
+        ];
+        let mut config = SequenceConfig {
+            include_summer: false,
+            max_courses_per_term: Some(1),
+            starting_semester: Season::Fall,
+            starting_year: 2023,
+            max_terms: None,
+            max_credits_per_term: None,
+            min_credits_per_term: None,
+        };
+
+        assert_eq!(
+            validation_report(&courses, &config).issues,
+            vec![SequenceError::CorequisiteGroupTooLarge {
+                courses: vec![courses[0].info(), courses[1].info()],
+                max_courses_per_term: 1,
+            }]
+        );
+
+        config.max_courses_per_term = Some(2);
+
+        assert_eq!(
+            validation_report(&courses, &config).issues,
+            vec![SequenceError::CorequisitesNeverOfferedTogether {
+                courses: vec![courses[0].info(), courses[1].info()],
+            }]
+        );
+    }
+
+    #[test]
+    fn report_should_flag_conflicting_antirequisites() {
+        let courses = vec![
+            Course {
+                subject_code: "MAT".to_string(),
+                name: String::from("Calculus for science"),
+                catalog_code: 1330,
+                prerequisites: None,
+                corequisites: None,
+                antirequisites: vec![],
+                equivalents: vec![],
+                unparsed_prerequisites: vec![],
+                credits: 3.0,
+                terms_offered: HashMap::from([(Season::Fall, true)]),
+            },
+            Course {
+                subject_code: "MAT".to_string(),
+                name: String::from("Calculus for engineers"),
+                catalog_code: 1320,
+                prerequisites: None,
+                corequisites: None,
+                antirequisites: vec![CourseNode {
+                    subject_code: "MAT".to_string(),
+                    catalog_code: 1330,
+                }],
+                equivalents: vec![],
+                unparsed_prerequisites: vec![],
+                credits: 3.0,
+                terms_offered: HashMap::from([(Season::Fall, true)]),
+            },
+        ];
+        let config = SequenceConfig {
+            include_summer: false,
+            max_courses_per_term: Some(5),
+            starting_semester: Season::Fall,
+            starting_year: 2023,
+            max_terms: None,
+            max_credits_per_term: None,
+            min_credits_per_term: None,
+        };
+
+        assert_eq!(
+            validation_report(&courses, &config).issues,
+            vec![SequenceError::ConflictingAntirequisites {
+                course: courses[1].info(),
+                antirequisite: courses[0].info(),
+            }]
+        );
+    }
+
+    #[test]
+    fn completed_courses_satisfy_prerequisites() {
+        let courses = vec![
+            Course {
+                subject_code: "CSI".to_string(),
+                name: String::from("A computing course"),
+                catalog_code: 1100,
+                prerequisites: None,
+                corequisites: None,
+                antirequisites: vec![],
+                equivalents: vec![],
+                unparsed_prerequisites: vec![],
+                credits: 3.0,
+                terms_offered: HashMap::from([(Season::Fall, true)]),
+            },
+            Course {
+                subject_code: "CSI".to_string(),
+                name: String::from("A computing course"),
+                catalog_code: 1300,
+                prerequisites: Some(PrerequisiteTree::CourseNode(CourseNode {
+                    subject_code: "MAT".to_string(),
+                    catalog_code: 1341,
+                })),
+                corequisites: None,
+                antirequisites: vec![],
+                equivalents: vec![],
+                unparsed_prerequisites: vec![],
+                credits: 3.0,
+                terms_offered: HashMap::from([(Season::Winter, true)]),
+            },
+        ];
+        let completed = vec![
+            "CSI 1100".parse::<CompletedCourse>().unwrap(),
+            "MAT 1341".parse::<CompletedCourse>().unwrap(),
+        ];
+        let config = SequenceConfig {
+            include_summer: false,
+            max_courses_per_term: Some(5),
+            starting_semester: Season::Fall,
+            starting_year: 2023,
+            max_terms: None,
+            max_credits_per_term: None,
+            min_credits_per_term: None,
+        };
+
+        assert!(validate_input(&courses, &[], &config).is_err());
+
+        let (remaining, taken) = split_completed(courses.clone(), &completed);
+
+        assert_eq!(remaining, vec![courses[1].clone()]);
+        assert_eq!(taken[0], courses[0]);
+        assert_eq!(taken[1].name, "Transfer credit");
+        assert!(validate_input(&remaining, &taken, &config).is_ok());
+    }
+
+    #[test]
+    fn report_should_flag_prerequisite_cycles_not_broken_by_an_alternative() {
+        let node = |catalog_code| PrerequisiteTree::course("CSI", catalog_code);
+        let mut courses = vec![
+            Course::test("CSI", 2110).with_prerequisites(node(2120)),
+            Course::test("CSI", 2120).with_prerequisites(node(3105)),
+            Course::test("CSI", 3105).with_prerequisites(PrerequisiteTree::OrNode(LogicNode::new(
+                node(2110),
+                node(1100),
+            ))),
+            Course::test("CSI", 4000).with_prerequisites(node(2110)),
+        ];
+        let config = SequenceConfig {
+            include_summer: false,
+            max_courses_per_term: Some(5),
+            starting_semester: Season::Fall,
+            starting_year: 2023,
+            max_terms: None,
+            max_credits_per_term: None,
+            min_credits_per_term: None,
+        };
+
+        let report = validation_report(&courses, &config);
+
+        assert_eq!(
+            report.issues,
+            vec![SequenceError::PrerequisiteCycle {
+                cycle: vec![
+                    courses[0].info(),
+                    courses[1].info(),
+                    courses[2].info(),
+                    courses[0].info(),
+                ],
+            }]
         );
+        assert_eq!(
+            report.issues[0].to_string(),
+            "Prerequisites form a cycle: CSI 2110 -> CSI 2120 -> CSI 3105 -> CSI 2110"
+        );
+
+        courses.push(Course::test("CSI", 1100));
+
+        assert!(validation_report(&courses, &config).is_valid());
     }
 }
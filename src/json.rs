@@ -0,0 +1,144 @@
+use serde::Deserialize;
+
+use super::{
+    course::{Course, CourseJson},
+    error::SequenceError,
+};
+
+/// A course catalog in JSON, of the form
+///
+/// ```json
+/// {
+///   "courses": [
+///     {
+///       "subject_code": "CSI",
+///       "catalog_code": 2110,
+///       "name": "Data Structures and Algorithms",
+///       "credits": 3.0,
+///       "prerequisites": "CSI 1101 or CSI 1501, ITI 1121.",
+///       "corequisites": {"type": "course", "subject_code": "MAT", "catalog_code": 1348},
+///       "antirequisites": [{"subject_code": "CSI", "catalog_code": 2114}],
+///       "equivalents": [],
+///       "terms_offered": ["Fall", "Winter"]
+///     }
+///   ]
+/// }
+/// ```
+///
+/// `prerequisites` and `corequisites` take either catalog text or a tree in the
+/// schema `PrerequisiteTree` is serialized with. Only the codes, name and
+/// `terms_offered` are required; `credits` defaults to 3 units.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Catalog {
+    pub courses: Vec<CourseJson>,
+}
+
+impl Catalog {
+    /// Converts every entry, failing on the first whose requisites can not be parsed
+    pub fn into_courses(self) -> Result<Vec<Course>, SequenceError> {
+        self.courses.into_iter().map(Course::from_json).collect()
+    }
+}
+
+pub fn parse_json_to_courses(input: &str) -> Result<Vec<Course>, SequenceError> {
+    let catalog: Catalog = serde_json::from_str(input)?;
+
+    catalog.into_courses()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        prerequisite_tree::{CourseNode, LogicNode, PrerequisiteTree},
+        term::Season,
+    };
+
+    use super::*;
+
+    #[test]
+    fn parse_json_test() {
+        let json = r#"{
+            "courses": [
+                {
+                    "subject_code": "CSI",
+                    "catalog_code": 2110,
+                    "name": "Data Structures and Algorithms",
+                    "prerequisites": "CSI 1101, ITI 1121.",
+                    "terms_offered": ["Fall", "Winter"]
+                },
+                {
+                    "subject_code": "CSI",
+                    "catalog_code": 2132,
+                    "course_name": "Databases I",
+                    "credits": 1.5,
+                    "prerequisites": {"type": "course", "subject_code": "CSI", "catalog_code": 2110},
+                    "corequisites": "MAT 1348.",
+                    "antirequisites": [{"subject_code": "CSI", "catalog_code": 2532}],
+                    "terms_offered": ["Summer"]
+                }
+            ]
+        }"#;
+
+        let courses = parse_json_to_courses(json).unwrap();
+
+        assert_eq!(
+            courses[0].prerequisites,
+            Some(PrerequisiteTree::AndNode(LogicNode::new(
+                PrerequisiteTree::CourseNode(CourseNode {
+                    subject_code: "CSI".to_string(),
+                    catalog_code: 1101,
+                }),
+                PrerequisiteTree::CourseNode(CourseNode {
+                    subject_code: "ITI".to_string(),
+                    catalog_code: 1121,
+                }),
+            )))
+        );
+        assert_eq!(courses[0].credits, 3.0);
+        assert!(courses[0].terms_offered[&Season::Fall]);
+        assert!(!courses[0].terms_offered[&Season::Summer]);
+
+        assert_eq!(courses[1].name, "Databases I");
+        assert_eq!(courses[1].credits, 1.5);
+        assert_eq!(
+            courses[1].prerequisites,
+            Some(PrerequisiteTree::CourseNode(CourseNode {
+                subject_code: "CSI".to_string(),
+                catalog_code: 2110,
+            }))
+        );
+        assert!(courses[1].corequisites.is_some());
+        assert_eq!(courses[1].antirequisites.len(), 1);
+    }
+
+    #[test]
+    fn parse_json_errors_test() {
+        assert!(matches!(
+            parse_json_to_courses("{\"courses\": [{\"subject_code\": \"CSI\"}]}"),
+            Err(SequenceError::InvalidJson { line: 1, .. })
+        ));
+
+        let json = r#"{"courses": [{"subject_code": "CSI", "catalog_code": 2110, "name": "A course",
+            "prerequisites": "CSI 1101; ITI 1121.", "terms_offered": []}]}"#;
+
+        assert!(matches!(
+            parse_json_to_courses(json),
+            Err(SequenceError::PrerequisiteParse(error)) if error.offset == Some(8)
+        ));
+    }
+
+    #[test]
+    fn deserialize_course_test() {
+        let course: Course = serde_json::from_str(
+            r#"{"subject_code": "CSI", "catalog_code": 2110, "name": "A course", "terms_offered": ["Fall"]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(course.catalog_code, 2110);
+        assert!(serde_json::from_str::<Course>(
+            r#"{"subject_code": "CSI", "catalog_code": 2110, "name": "A course",
+                "prerequisites": "CSI 1101 with permission.", "terms_offered": ["Fall"]}"#,
+        )
+        .is_err());
+    }
+}
@@ -1,7 +1,9 @@
 use config::SequenceConfig;
-use course::Course;
-use input::validate_input;
-use prerequisites::validate_prerequisites;
+use course::{CompletedCourse, Course};
+use critical_path::{course_priorities, priority_key, required_indices};
+use error::SequenceError;
+use input::{split_completed, stuck_courses, validate_input};
+use prerequisites::{validate_corequisites, validate_prerequisites};
 use serde::Serialize;
 use term::{Season, Term};
 
@@ -12,12 +14,33 @@ pub mod term;
 
 /* UTILS */
 pub mod config;
+pub mod critical_path;
 pub mod csv;
+pub mod error;
+pub mod graph;
+pub mod ical;
 pub mod input;
+pub mod json;
 pub mod prerequisites;
+pub mod program;
+pub mod report;
+pub mod requirements;
+
+/* SEQUENCERS */
+pub mod optimal;
 
 pub trait Sequence {
-    fn sequence(&self, courses: Vec<Course>) -> Result<Vec<Term>, String>;
+    fn sequence(&self, courses: Vec<Course>) -> Result<Vec<Term>, SequenceError> {
+        self.sequence_with_completed(courses, &[])
+    }
+
+    /// Sequences the courses not yet completed, counting completed courses and
+    /// transfer credits towards prerequisites without scheduling them
+    fn sequence_with_completed(
+        &self,
+        courses: Vec<Course>,
+        completed: &[CompletedCourse],
+    ) -> Result<Vec<Term>, SequenceError>;
 }
 
 #[derive(Serialize)]
@@ -36,55 +59,197 @@ impl Sequencer {
             include_summer,
             starting_semester,
             starting_year,
-            max_courses_per_term,
+            max_courses_per_term: Some(max_courses_per_term),
+            max_terms: None,
+            max_credits_per_term: None,
+            min_credits_per_term: None,
         };
 
         Sequencer { config }
     }
+
+    pub fn from_config(config: SequenceConfig) -> Self {
+        Sequencer { config }
+    }
+
+    /// Lifts the limit on the number of courses in a term, leaving terms to be
+    /// limited by credits alone
+    pub fn without_course_limit(mut self) -> Self {
+        self.config.max_courses_per_term = None;
+        self
+    }
+
+    /// Limits the sequence to at most `max_terms` terms
+    pub fn with_max_terms(mut self, max_terms: u32) -> Self {
+        self.config.max_terms = Some(max_terms);
+        self
+    }
+
+    /// Limits the total units of the courses in each term
+    pub fn with_max_credits_per_term(mut self, max_credits: f32) -> Self {
+        self.config.max_credits_per_term = Some(max_credits);
+        self
+    }
+
+    /// Requires every term but the last to have at least `min_credits` units,
+    /// leaving terms that can not reach it empty
+    pub fn with_min_credits_per_term(mut self, min_credits: f32) -> Self {
+        self.config.min_credits_per_term = Some(min_credits);
+        self
+    }
+}
+
+impl Sequencer {
+    /// The courses that have to be added to the term along with `courses[index]`
+    /// for its corequisites to be met, including itself, if they can all be added
+    fn bundle(
+        &self,
+        index: usize,
+        courses: &[Course],
+        courses_taken: &[Course],
+        term: &Term,
+    ) -> Option<Vec<usize>> {
+        let mut bundle = vec![index];
+        let mut next = 0;
+        while next < bundle.len() {
+            for required in required_indices(&courses[bundle[next]].corequisites, courses) {
+                if !bundle.contains(&required) {
+                    bundle.push(required);
+                }
+            }
+            next += 1;
+        }
+
+        let with_bundle: Vec<Course> = term
+            .courses
+            .iter()
+            .chain(bundle.iter().map(|&i| &courses[i]))
+            .cloned()
+            .collect();
+        let credits: f32 = with_bundle.iter().map(|c| c.credits).sum();
+
+        let fits = self.config.allows_courses(with_bundle.len())
+            && self
+                .config
+                .max_credits_per_term
+                .is_none_or(|max| credits <= max);
+        let can_take = bundle.iter().map(|&i| &courses[i]).all(|c| {
+            *c.terms_offered.get(&term.season).unwrap_or(&false)
+                && !courses_taken
+                    .iter()
+                    .chain(term.courses.iter())
+                    .any(|other| c.conflicts_with(other))
+                && validate_prerequisites(&c.prerequisites, courses_taken)
+                && validate_corequisites(&c.corequisites, courses_taken, &with_bundle)
+        });
+
+        (fits && can_take).then_some(bundle)
+    }
 }
 
 impl Sequence for Sequencer {
-    fn sequence(&self, mut courses: Vec<Course>) -> Result<Vec<Term>, String> {
+    fn sequence_with_completed(
+        &self,
+        courses: Vec<Course>,
+        completed: &[CompletedCourse],
+    ) -> Result<Vec<Term>, SequenceError> {
+        let (courses, completed) = split_completed(courses, completed);
+
         // Make sure we can in fact sequence the courses given the config
-        if let Err(err) = validate_input(&courses, &self.config) {
-            return Err(err);
-        }
+        validate_input(&courses, &completed, &self.config)?;
 
-        // Sort courses by year, this is done as a heuristic to improve sequencing
-        courses.sort_by(|a, b| a.catalog_code.cmp(&b.catalog_code));
+        // Sort courses so that those heading the longest prerequisite chains are
+        // picked first, falling back to year order
+        let priorities = course_priorities(&courses);
+        let mut prioritised: Vec<(Course, _)> = courses.into_iter().zip(priorities).collect();
+        prioritised.sort_by_key(|(c, priority)| priority_key(priority, c.catalog_code));
+        let mut courses: Vec<Course> = prioritised.into_iter().map(|(c, _)| c).collect();
 
         // Instatiate some necessary variables
         let mut result: Vec<Term> = vec![];
-        let mut courses_taken: Vec<Course> = vec![];
+        let mut courses_taken: Vec<Course> = completed;
 
         // Set starting year and season
         let mut current_season = self.config.starting_semester;
         let mut current_year = self.config.starting_year;
 
+        // Once every season has passed without a course being added, nothing will change
+        let seasons_per_year = if self.config.include_summer { 3 } else { 2 };
+        let mut terms_without_progress = 0;
+
+        // The first term since the last progress that was skipped for falling
+        // short of the minimum load
+        let mut shortfall = None;
+
         while !courses.is_empty() {
+            if let Some(max_terms) = self.config.max_terms {
+                if result.len() >= max_terms as usize {
+                    return Err(SequenceError::MaxTermsExceeded {
+                        max_terms,
+                        remaining: courses.iter().map(Course::info).collect(),
+                    });
+                }
+            }
+
             let mut current_term = Term::new(current_season, current_year, vec![]);
+            let unscheduled = courses.clone();
 
-            while current_term.courses.len() < self.config.max_courses_per_term as usize {
-                // If there is a course we can take, add it to the courses in the term
-                if let Some(next_course_index) = courses.iter().position(|c| {
-                    validate_prerequisites(&c.prerequisites, &courses_taken)
-                        && *c.terms_offered.get(&current_season).unwrap()
-                }) {
-                    let next_course = courses.remove(next_course_index);
-                    current_term.courses.push(next_course);
+            while self.config.allows_courses(current_term.courses.len() + 1) {
+                // If there is a course we can take, add it and any corequisites
+                // it needs to the courses in the term
+                if let Some(mut bundle) = (0..courses.len())
+                    .find_map(|i| self.bundle(i, &courses, &courses_taken, &current_term))
+                {
+                    bundle.sort_unstable();
+                    let added: Vec<Course> =
+                        bundle.iter().rev().map(|&i| courses.remove(i)).collect();
+                    current_term.courses.extend(added.into_iter().rev());
                 } else {
                     break;
                 }
             }
 
+            // Every term but the last has to meet the minimum load, so a term
+            // that falls short is skipped in the hope a later season offers more
+            if let Some(min_credits) = self.config.min_credits_per_term {
+                let credits = current_term.credits();
+                if !courses.is_empty() && !current_term.courses.is_empty() && credits < min_credits
+                {
+                    shortfall.get_or_insert(SequenceError::BelowMinimumCredits {
+                        season: current_season,
+                        year: current_year,
+                        credits,
+                        min_credits,
+                    });
+                    courses = unscheduled;
+                    current_term.courses.clear();
+                }
+            }
+
+            if current_term.courses.is_empty() {
+                terms_without_progress += 1;
+                if terms_without_progress >= seasons_per_year {
+                    return Err(shortfall.unwrap_or_else(|| SequenceError::Infeasible {
+                        stuck: stuck_courses(&courses, &courses_taken, &self.config),
+                    }));
+                }
+            } else {
+                terms_without_progress = 0;
+                shortfall = None;
+            }
+
             courses_taken.extend(current_term.courses.clone());
 
             result.push(current_term);
             current_season = current_season.next(self.config.include_summer);
 
-            match current_season {
-                Season::Winter => current_year += 1,
-                _ => (),
+            if current_season == Season::Winter && !courses.is_empty() {
+                current_year =
+                    current_year
+                        .checked_add(1)
+                        .ok_or_else(|| SequenceError::InvalidConfig {
+                            message: format!("the sequence can not run past the year {}", u32::MAX),
+                        })?;
             }
         }
 
@@ -97,7 +262,8 @@ mod test {
     use std::collections::HashMap;
 
     use crate::{
-        course::Course,
+        course::{CompletedCourse, Course},
+        error::{SequenceError, StuckCourse, StuckReason},
         prerequisite_tree::{CourseNode, LogicNode, PrerequisiteTree},
         term::Season,
         Sequence, Sequencer,
@@ -111,6 +277,11 @@ mod test {
                 name: String::from("A computing course"),
                 catalog_code: 1111,
                 prerequisites: None,
+                corequisites: None,
+                antirequisites: vec![],
+                equivalents: vec![],
+                unparsed_prerequisites: vec![],
+                credits: 3.0,
                 terms_offered: HashMap::from([
                     (Season::Winter, false),
                     (Season::Summer, true),
@@ -125,6 +296,11 @@ mod test {
                     subject_code: "CSI".to_string(),
                     catalog_code: 1111,
                 })),
+                corequisites: None,
+                antirequisites: vec![],
+                equivalents: vec![],
+                unparsed_prerequisites: vec![],
+                credits: 3.0,
                 terms_offered: HashMap::from([
                     (Season::Winter, true),
                     (Season::Summer, true),
@@ -139,6 +315,11 @@ mod test {
                     subject_code: "MAT".to_string(),
                     catalog_code: 1111,
                 })),
+                corequisites: None,
+                antirequisites: vec![],
+                equivalents: vec![],
+                unparsed_prerequisites: vec![],
+                credits: 3.0,
                 terms_offered: HashMap::from([
                     (Season::Winter, true),
                     (Season::Summer, true),
@@ -150,6 +331,11 @@ mod test {
                 name: String::from("A math course"),
                 catalog_code: 1111,
                 prerequisites: None,
+                corequisites: None,
+                antirequisites: vec![],
+                equivalents: vec![],
+                unparsed_prerequisites: vec![],
+                credits: 3.0,
                 terms_offered: HashMap::from([
                     (Season::Winter, false),
                     (Season::Summer, true),
@@ -170,6 +356,11 @@ mod test {
                         catalog_code: 1113,
                     }),
                 ))),
+                corequisites: None,
+                antirequisites: vec![],
+                equivalents: vec![],
+                unparsed_prerequisites: vec![],
+                credits: 3.0,
                 terms_offered: HashMap::from([
                     (Season::Winter, false),
                     (Season::Summer, true),
@@ -181,6 +372,11 @@ mod test {
                 name: String::from("A physics course"),
                 catalog_code: 1111,
                 prerequisites: None,
+                corequisites: None,
+                antirequisites: vec![],
+                equivalents: vec![],
+                unparsed_prerequisites: vec![],
+                credits: 3.0,
                 terms_offered: HashMap::from([
                     (Season::Winter, true),
                     (Season::Summer, false),
@@ -195,4 +391,422 @@ mod test {
 
         assert_eq!(result.len(), 3);
     }
+
+    #[test]
+    fn prioritises_courses_heading_long_chains() {
+        let offered = HashMap::from([
+            (Season::Winter, true),
+            (Season::Summer, true),
+            (Season::Fall, true),
+        ]);
+        let courses = vec![
+            Course {
+                subject_code: "CSI".to_string(),
+                name: String::from("An elective"),
+                catalog_code: 1000,
+                prerequisites: None,
+                corequisites: None,
+                antirequisites: vec![],
+                equivalents: vec![],
+                unparsed_prerequisites: vec![],
+                credits: 3.0,
+                terms_offered: offered.clone(),
+            },
+            Course {
+                subject_code: "CSI".to_string(),
+                name: String::from("A core course"),
+                catalog_code: 1500,
+                prerequisites: None,
+                corequisites: None,
+                antirequisites: vec![],
+                equivalents: vec![],
+                unparsed_prerequisites: vec![],
+                credits: 3.0,
+                terms_offered: offered.clone(),
+            },
+            Course {
+                subject_code: "CSI".to_string(),
+                name: String::from("A follow-up course"),
+                catalog_code: 2000,
+                prerequisites: Some(PrerequisiteTree::CourseNode(CourseNode {
+                    subject_code: "CSI".to_string(),
+                    catalog_code: 1500,
+                })),
+                corequisites: None,
+                antirequisites: vec![],
+                equivalents: vec![],
+                unparsed_prerequisites: vec![],
+                credits: 3.0,
+                terms_offered: offered,
+            },
+        ];
+
+        let result = Sequencer::new(false, Season::Fall, 2023, 1)
+            .sequence(courses)
+            .unwrap();
+
+        assert_eq!(result[0].courses[0].catalog_code, 1500);
+        assert_eq!(result.len(), 3);
+    }
+
+    #[test]
+    fn errors_instead_of_looping_when_courses_are_stuck() {
+        let courses = vec![
+            Course::test("CSI", 1000),
+            Course::test("CSI", 2000).with_prerequisites(PrerequisiteTree::course("CSI", 2001)),
+            Course::test("CSI", 2001).with_prerequisites(PrerequisiteTree::course("CSI", 2000)),
+        ];
+
+        let result = Sequencer::new(false, Season::Fall, 2023, 3).sequence(courses.clone());
+
+        assert_eq!(
+            result.unwrap_err(),
+            SequenceError::PrerequisiteCycle {
+                cycle: vec![courses[1].info(), courses[2].info(), courses[1].info()],
+            }
+        );
+    }
+
+    #[test]
+    fn errors_instead_of_running_past_the_last_year() {
+        let courses = vec![
+            Course::test("CSI", 1000),
+            Course::test("CSI", 2000).with_prerequisites(PrerequisiteTree::course("CSI", 1000)),
+        ];
+        let sequencer = Sequencer::new(false, Season::Fall, u32::MAX, 3);
+
+        assert!(matches!(
+            sequencer.sequence(courses.clone()),
+            Err(SequenceError::InvalidConfig { .. })
+        ));
+        assert!(sequencer.sequence(courses[..1].to_vec()).is_ok());
+    }
+
+    #[test]
+    fn errors_when_corequisites_can_never_be_placed() {
+        let courses = vec![
+            Course::test("CSI", 1000),
+            Course::test("CSI", 2000)
+                // Needs CSI 2001 alongside it, which needs CSI 2000 first
+                .with_corequisites(PrerequisiteTree::course("CSI", 2001)),
+            Course::test("CSI", 2001).with_prerequisites(PrerequisiteTree::course("CSI", 2000)),
+        ];
+
+        let result = Sequencer::new(false, Season::Fall, 2023, 3).sequence(courses.clone());
+
+        assert_eq!(
+            result.unwrap_err(),
+            SequenceError::Infeasible {
+                stuck: vec![
+                    StuckCourse {
+                        course: courses[1].info(),
+                        reason: StuckReason::CorequisitesUnreachable,
+                    },
+                    StuckCourse {
+                        course: courses[2].info(),
+                        reason: StuckReason::PrerequisitesUnreachable,
+                    },
+                ]
+            }
+        );
+    }
+
+    #[test]
+    fn explains_corequisites_that_exceed_the_unit_limit() {
+        let courses = vec![
+            Course::test("CSI", 2000)
+                .with_credits(6.0)
+                .with_corequisites(PrerequisiteTree::course("CSI", 2001)),
+            Course::test("CSI", 2001)
+                .with_credits(6.0)
+                .with_corequisites(PrerequisiteTree::course("CSI", 2000)),
+        ];
+
+        let result = Sequencer::new(false, Season::Fall, 2023, 5)
+            .with_max_credits_per_term(9.0)
+            .sequence(courses);
+
+        match result {
+            Err(SequenceError::Infeasible { stuck }) => {
+                assert_eq!(stuck.len(), 2);
+                assert!(stuck
+                    .iter()
+                    .all(|stuck| stuck.reason == StuckReason::ExceedsTermLimits));
+            }
+            other => panic!("expected courses to be stuck, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn errors_with_the_courses_held_back_behind_stuck_courses() {
+        let courses = vec![
+            Course::test("CSI", 1000),
+            Course::test("CSI", 2000)
+                .with_credits(6.0)
+                .with_corequisites(PrerequisiteTree::course("CSI", 2001)),
+            Course::test("CSI", 2001)
+                .with_credits(6.0)
+                .with_corequisites(PrerequisiteTree::course("CSI", 2000)),
+            Course::test("CSI", 3000).with_prerequisites(PrerequisiteTree::course("CSI", 2000)),
+        ];
+
+        let result = Sequencer::new(false, Season::Fall, 2023, 5)
+            .with_max_credits_per_term(9.0)
+            .sequence(courses);
+
+        match result {
+            Err(SequenceError::Infeasible { stuck }) => {
+                let reason = |catalog_code| {
+                    stuck
+                        .iter()
+                        .find(|stuck| stuck.course.catalog_code == catalog_code)
+                        .map(|stuck| stuck.reason)
+                };
+                assert_eq!(stuck.len(), 3);
+                assert_eq!(reason(2000), Some(StuckReason::ExceedsTermLimits));
+                assert_eq!(reason(2001), Some(StuckReason::ExceedsTermLimits));
+                assert_eq!(reason(3000), Some(StuckReason::PrerequisitesUnreachable));
+            }
+            other => panic!("expected courses to be stuck, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn errors_when_max_terms_is_exceeded() {
+        let courses = (0..4)
+            .map(|i| Course {
+                subject_code: "CSI".to_string(),
+                name: String::from("A computing course"),
+                catalog_code: 1000 + i,
+                prerequisites: None,
+                corequisites: None,
+                antirequisites: vec![],
+                equivalents: vec![],
+                unparsed_prerequisites: vec![],
+                credits: 3.0,
+                terms_offered: HashMap::from([(Season::Fall, true), (Season::Winter, true)]),
+            })
+            .collect();
+
+        let result = Sequencer::new(false, Season::Fall, 2023, 1)
+            .with_max_terms(3)
+            .sequence(courses);
+
+        match result {
+            Err(SequenceError::MaxTermsExceeded {
+                max_terms,
+                remaining,
+            }) => {
+                assert_eq!(max_terms, 3);
+                assert_eq!(remaining.len(), 1);
+            }
+            other => panic!("expected max terms to be exceeded, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn skips_completed_courses_and_counts_transfer_credits() {
+        let prerequisite = |subject_code: &str, catalog_code| {
+            PrerequisiteTree::CourseNode(CourseNode {
+                subject_code: subject_code.to_string(),
+                catalog_code,
+            })
+        };
+        let courses = vec![
+            Course {
+                subject_code: "CSI".to_string(),
+                name: String::from("A computing course"),
+                catalog_code: 1000,
+                prerequisites: None,
+                corequisites: None,
+                antirequisites: vec![],
+                equivalents: vec![],
+                unparsed_prerequisites: vec![],
+                credits: 3.0,
+                terms_offered: HashMap::from([(Season::Fall, true)]),
+            },
+            Course {
+                subject_code: "CSI".to_string(),
+                name: String::from("A computing course"),
+                catalog_code: 2000,
+                prerequisites: Some(PrerequisiteTree::AndNode(LogicNode::new(
+                    prerequisite("CSI", 1000),
+                    prerequisite("MAT", 1341),
+                ))),
+                corequisites: None,
+                antirequisites: vec![],
+                equivalents: vec![],
+                unparsed_prerequisites: vec![],
+                credits: 3.0,
+                terms_offered: HashMap::from([(Season::Fall, true)]),
+            },
+        ];
+        let completed = vec![
+            "CSI 1000".parse::<CompletedCourse>().unwrap(),
+            "MAT 1341".parse::<CompletedCourse>().unwrap(),
+        ];
+
+        let result = Sequencer::new(false, Season::Fall, 2023, 5)
+            .sequence_with_completed(courses.clone(), &completed)
+            .unwrap();
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].courses, vec![courses[1].clone()]);
+    }
+
+    #[test]
+    fn respects_max_credits_per_term() {
+        let courses: Vec<Course> = [6.0, 3.0, 3.0, 1.5, 1.5]
+            .iter()
+            .enumerate()
+            .map(|(i, credits)| Course {
+                subject_code: "CSI".to_string(),
+                name: String::from("A computing course"),
+                catalog_code: 1000 + i as u32,
+                prerequisites: None,
+                corequisites: None,
+                antirequisites: vec![],
+                equivalents: vec![],
+                unparsed_prerequisites: vec![],
+                credits: *credits,
+                terms_offered: HashMap::from([(Season::Fall, true), (Season::Winter, true)]),
+            })
+            .collect();
+
+        let result = Sequencer::new(false, Season::Fall, 2023, 1)
+            .without_course_limit()
+            .with_max_credits_per_term(9.0)
+            .sequence(courses)
+            .unwrap();
+
+        assert_eq!(result.len(), 2);
+        assert!(result.iter().all(|term| term.credits() <= 9.0));
+    }
+
+    #[test]
+    fn errors_when_term_is_below_minimum_credits() {
+        let courses = vec![
+            Course {
+                subject_code: "CSI".to_string(),
+                name: String::from("A computing course"),
+                catalog_code: 1000,
+                prerequisites: None,
+                corequisites: None,
+                antirequisites: vec![],
+                equivalents: vec![],
+                unparsed_prerequisites: vec![],
+                credits: 3.0,
+                terms_offered: HashMap::from([(Season::Fall, true), (Season::Winter, true)]),
+            },
+            Course {
+                subject_code: "CSI".to_string(),
+                name: String::from("A computing course"),
+                catalog_code: 2000,
+                prerequisites: Some(PrerequisiteTree::CourseNode(CourseNode {
+                    subject_code: "CSI".to_string(),
+                    catalog_code: 1000,
+                })),
+                corequisites: None,
+                antirequisites: vec![],
+                equivalents: vec![],
+                unparsed_prerequisites: vec![],
+                credits: 3.0,
+                terms_offered: HashMap::from([(Season::Fall, true), (Season::Winter, true)]),
+            },
+        ];
+
+        let result = Sequencer::new(false, Season::Fall, 2023, 5)
+            .with_min_credits_per_term(6.0)
+            .sequence(courses);
+
+        assert_eq!(
+            result.unwrap_err(),
+            SequenceError::BelowMinimumCredits {
+                season: Season::Fall,
+                year: 2023,
+                credits: 3.0,
+                min_credits: 6.0,
+            }
+        );
+    }
+
+    #[test]
+    fn leaves_terms_empty_when_they_can_not_reach_minimum_credits() {
+        let courses = (0..6).map(|i| Course::test("CSI", 1000 + i)).collect();
+
+        let result = Sequencer::new(true, Season::Fall, 2023, 2)
+            .with_min_credits_per_term(6.0)
+            .sequence(courses)
+            .unwrap();
+
+        assert_eq!(result.len(), 4);
+        assert_eq!(result[2].season, Season::Summer);
+        assert!(result[2].courses.is_empty());
+        assert!(result
+            .iter()
+            .all(|term| term.courses.is_empty() || term.credits() >= 6.0));
+    }
+
+    #[test]
+    fn defers_terms_below_minimum_credits_to_a_later_season() {
+        let courses = vec![
+            Course::test("CSI", 1000),
+            Course::test("CSI", 1001).offered_in(&[Season::Winter]),
+            Course::test("CSI", 2000).with_prerequisites(PrerequisiteTree::course("CSI", 1000)),
+        ];
+
+        let result = Sequencer::new(false, Season::Fall, 2023, 2)
+            .with_min_credits_per_term(6.0)
+            .sequence(courses)
+            .unwrap();
+
+        assert_eq!(result.len(), 3);
+        assert!(result[0].courses.is_empty());
+        assert_eq!(result[1].credits(), 6.0);
+        assert_eq!(result[2].courses[0].catalog_code, 2000);
+    }
+
+    #[test]
+    fn places_corequisites_in_the_same_term() {
+        let offered = HashMap::from([(Season::Fall, true), (Season::Winter, true)]);
+        let corequisite = |catalog_code| {
+            Some(PrerequisiteTree::CourseNode(CourseNode {
+                subject_code: "PHY".to_string(),
+                catalog_code,
+            }))
+        };
+        let courses = vec![
+            Course {
+                subject_code: "PHY".to_string(),
+                name: String::from("A physics lab"),
+                catalog_code: 1322,
+                prerequisites: None,
+                corequisites: corequisite(1321),
+                antirequisites: vec![],
+                equivalents: vec![],
+                unparsed_prerequisites: vec![],
+                credits: 1.5,
+                terms_offered: offered.clone(),
+            },
+            Course {
+                subject_code: "PHY".to_string(),
+                name: String::from("A physics course"),
+                catalog_code: 1321,
+                prerequisites: None,
+                corequisites: corequisite(1322),
+                antirequisites: vec![],
+                equivalents: vec![],
+                unparsed_prerequisites: vec![],
+                credits: 3.0,
+                terms_offered: offered,
+            },
+        ];
+
+        let result = Sequencer::new(false, Season::Fall, 2023, 2)
+            .sequence(courses)
+            .unwrap();
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].courses.len(), 2);
+    }
 }
@@ -1,5 +1,9 @@
 use models::RequestTimer;
+use rocket::data::{Data, Limits};
 use rocket::form::Form;
+use rocket::fs::TempFile;
+use rocket::http::Accept;
+use rocket::http::ContentType;
 use rocket::http::Method;
 use rocket::http::Status;
 use rocket::response::status;
@@ -8,9 +12,16 @@ use rocket::tokio::io::AsyncReadExt;
 
 use rocket_cors::{AllowedOrigins, CorsOptions};
 
-use models::RequestBody;
-use usequence::csv::parse_csv_to_courses;
-use usequence::term::Term;
+use models::{
+    ClusteringForm, GraphFormat, GraphRequestBody, JsonRequestBody, OutputFormat, RequestBody,
+    RequirementsRequestBody, SequenceResponse,
+};
+use usequence::csv::parse_csv_to_courses_with_options;
+use usequence::error::SequenceError;
+use usequence::graph::{Clustering, CourseGraph};
+use usequence::input::{split_completed, validation_report_with_completed, ValidationReport};
+use usequence::json::Catalog;
+use usequence::requirements::Requirements;
 use usequence::Sequence;
 use usequence::Sequencer;
 
@@ -19,41 +30,171 @@ mod models;
 #[macro_use]
 extern crate rocket;
 
+/// Reads an uploaded file as text
+async fn read_upload(file: &TempFile<'_>) -> Result<String, SequenceError> {
+    let mut buf = String::new();
+    file.open().await?.read_to_string(&mut buf).await?;
+    Ok(buf)
+}
+
+/// Rejects a request with the error found in it
+fn bad_request(e: SequenceError) -> status::Custom<Json<SequenceError>> {
+    status::Custom(Status::BadRequest, Json(e))
+}
+
 #[get("/heartbeat")]
 fn healthcheck() -> &'static str {
     "OK"
 }
 
-#[post("/sequence", data = "<body>")]
+#[post("/sequence", data = "<body>", rank = 2)]
 async fn sequence(
     body: Form<RequestBody<'_>>,
-) -> Result<status::Custom<Json<Vec<Term>>>, status::Custom<String>> {
-    let mut courses_input = body.courses.open().await.unwrap();
-    let mut buf = String::new();
+    accept: Option<&Accept>,
+) -> Result<SequenceResponse, status::Custom<Json<SequenceError>>> {
+    let buf = read_upload(&body.courses).await.map_err(bad_request)?;
+
+    let config = body.config().map_err(bad_request)?;
+    let completed = body.catalog.completed_courses().map_err(bad_request)?;
+    let csv_options = body.catalog.csv_options().map_err(bad_request)?;
+    let calendar = body.calendar().map_err(bad_request)?;
+
+    let sequencer = Sequencer::from_config(config);
+
+    let courses_to_sequence =
+        parse_csv_to_courses_with_options(&buf, &csv_options).map_err(bad_request)?;
 
-    if body.max_courses_per_term <= 0 {
+    let sequence = sequencer
+        .sequence_with_completed(courses_to_sequence, &completed)
+        .map_err(bad_request)?;
+
+    // The term dates come from the request, so a bad calendar is the client's error
+    SequenceResponse::new(
+        sequence,
+        OutputFormat::negotiate(body.format, accept),
+        &calendar,
+    )
+    .map_err(bad_request)
+}
+
+#[post("/sequence", format = "json", data = "<body>")]
+async fn sequence_json(
+    body: Data<'_>,
+    limits: &Limits,
+    accept: Option<&Accept>,
+) -> Result<SequenceResponse, status::Custom<Json<SequenceError>>> {
+    let limit = limits.get("json").unwrap_or(Limits::JSON);
+    let buf = body
+        .open(limit)
+        .into_string()
+        .await
+        .map_err(|e| bad_request(e.into()))?;
+    if !buf.is_complete() {
         return Err(status::Custom(
-            Status::BadRequest,
-            "Invalid request: max courses per term must be greater than 0".to_string(),
+            Status::PayloadTooLarge,
+            Json(SequenceError::UnreadableInput {
+                message: format!("the request body is larger than the {} limit", limit),
+            }),
         ));
     }
 
-    courses_input.read_to_string(&mut buf).await.unwrap();
+    let body: JsonRequestBody = serde_json::from_str(&buf).map_err(|e| bad_request(e.into()))?;
+    let config = body.options.config().map_err(bad_request)?;
+    let completed = body.completed_courses().map_err(bad_request)?;
 
-    let sequencer = Sequencer::new(
-        body.include_summer,
-        body.starting_semester.into(),
-        body.starting_year,
-        body.max_courses_per_term,
-    );
+    let sequencer = Sequencer::from_config(config);
 
-    let courses_to_sequence = parse_csv_to_courses(&buf).unwrap();
+    let courses_to_sequence = Catalog {
+        courses: body.courses,
+    }
+    .into_courses()
+    .map_err(bad_request)?;
 
     let sequence = sequencer
-        .sequence(courses_to_sequence)
-        .map_err(|e| status::Custom(Status::BadRequest, e))?;
+        .sequence_with_completed(courses_to_sequence, &completed)
+        .map_err(bad_request)?;
+
+    // The term dates come from the request, so a bad calendar is the client's error
+    SequenceResponse::new(
+        sequence,
+        OutputFormat::negotiate(body.format, accept),
+        &body.calendar,
+    )
+    .map_err(bad_request)
+}
+
+#[post("/validate", data = "<body>")]
+async fn validate(
+    body: Form<RequestBody<'_>>,
+) -> Result<Json<ValidationReport>, status::Custom<Json<SequenceError>>> {
+    let buf = read_upload(&body.courses).await.map_err(bad_request)?;
+
+    let config = body.config().map_err(bad_request)?;
+    let completed = body.catalog.completed_courses().map_err(bad_request)?;
+    let csv_options = body.catalog.csv_options().map_err(bad_request)?;
+
+    let courses = parse_csv_to_courses_with_options(&buf, &csv_options).map_err(bad_request)?;
+    let (remaining, taken) = split_completed(courses, &completed);
 
-    Ok(status::Custom(Status::Ok, Json(sequence)))
+    Ok(Json(validation_report_with_completed(
+        &remaining, &taken, &config,
+    )))
+}
+
+#[post("/graph?<format>&<cluster>", data = "<body>")]
+async fn graph(
+    body: Form<GraphRequestBody<'_>>,
+    format: Option<GraphFormat>,
+    cluster: Option<ClusteringForm>,
+) -> Result<(ContentType, String), status::Custom<Json<SequenceError>>> {
+    let buf = read_upload(&body.courses).await.map_err(bad_request)?;
+
+    let csv_options = body.catalog.csv_options().map_err(bad_request)?;
+
+    let courses = parse_csv_to_courses_with_options(&buf, &csv_options).map_err(bad_request)?;
+    let clustering = cluster.map_or(Clustering::None, Clustering::from);
+
+    let mut graph = CourseGraph::new(&courses);
+    // Clustering by term needs the terms of a generated sequence
+    if clustering == Clustering::Term {
+        let config = body.config().map_err(bad_request)?;
+        let completed = body.catalog.completed_courses().map_err(bad_request)?;
+        let sequence = Sequencer::from_config(config)
+            .sequence_with_completed(courses, &completed)
+            .map_err(bad_request)?;
+        graph = graph.with_terms(&sequence);
+    }
+
+    let format = format.unwrap_or_default();
+    let output = match format {
+        GraphFormat::Dot => graph.to_dot(clustering),
+        GraphFormat::Mermaid => graph.to_mermaid(clustering),
+    };
+
+    Ok((format.content_type(), output))
+}
+
+#[post("/requirements/<subject>/<catalog>", data = "<body>")]
+async fn requirements(
+    subject: &str,
+    catalog: u32,
+    body: Form<RequirementsRequestBody<'_>>,
+) -> Result<Json<Requirements>, status::Custom<Json<SequenceError>>> {
+    let buf = read_upload(&body.courses).await.map_err(bad_request)?;
+
+    let completed = body.catalog.completed_courses().map_err(bad_request)?;
+    let csv_options = body.catalog.csv_options().map_err(bad_request)?;
+
+    let courses = parse_csv_to_courses_with_options(&buf, &csv_options).map_err(bad_request)?;
+    let (remaining, taken) = split_completed(courses, &completed);
+
+    // Course codes are case-insensitive, e.g. /requirements/csi/2110
+    usequence::requirements::requirements(&subject.to_uppercase(), catalog, &remaining, &taken)
+        .map(Json)
+        .map_err(|e| match e {
+            SequenceError::UnknownCourse { .. } => status::Custom(Status::NotFound, Json(e)),
+            _ => bad_request(e),
+        })
 }
 
 #[launch]
@@ -71,5 +212,15 @@ fn rocket() -> _ {
     rocket::build()
         .attach(RequestTimer::default())
         .attach(cors.to_cors().unwrap())
-        .mount("/", routes![healthcheck, sequence])
+        .mount(
+            "/",
+            routes![
+                healthcheck,
+                sequence,
+                sequence_json,
+                validate,
+                graph,
+                requirements
+            ],
+        )
 }
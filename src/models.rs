@@ -1,23 +1,378 @@
+use std::ops::Deref;
 use std::time::Duration;
 use std::time::SystemTime;
 
 use rocket::fairing::{Fairing, Info, Kind};
-use rocket::http::Header;
-use rocket::{form::FromForm, fs::TempFile, Data, FromFormField, Request, Response};
+use rocket::form::{self, name::NameView, DataField, FromForm, Options, ValueField};
+use rocket::http::{Accept, ContentType, Header};
+use rocket::response::content::RawHtml;
+use rocket::serde::json::Json;
+use rocket::{fs::TempFile, Data, FromFormField, Request, Responder, Response};
 
 use serde::{Deserialize, Serialize};
 
-use usequence::term::Season;
+use usequence::config::SequenceConfig;
+use usequence::course::{parse_completed_courses, CompletedCourse, CourseJson};
+use usequence::csv::{sequence_to_csv, ColumnMapping, CsvOptions};
+use usequence::error::SequenceError;
+use usequence::graph::Clustering;
+use usequence::ical::{sequence_to_ical, TermCalendar};
+use usequence::report::sequence_to_html;
+use usequence::term::{Season, Term};
 
 #[derive(FromForm)]
 pub struct RequestBody<'f> {
     pub include_summer: bool,
     pub starting_semester: SeasonForm,
     pub starting_year: u32,
-    pub max_courses_per_term: u32,
+    pub max_courses_per_term: Option<u32>,
+    pub max_credits_per_term: Option<f32>,
+    pub min_credits_per_term: Option<f32>,
+    pub max_terms: Option<u32>,
+    #[field(name = "completed")]
+    #[field(name = "lenient")]
+    #[field(name = "columns")]
+    pub catalog: Flattened<CatalogForm>,
+    pub format: Option<OutputFormat>,
+    /// When terms start and end for calendar exports, e.g. "Fall=09-04..12-31"
+    pub calendar: Option<String>,
     pub courses: TempFile<'f>,
 }
 
+impl RequestBody<'_> {
+    pub fn config(&self) -> Result<SequenceConfig, SequenceError> {
+        SequenceOptions {
+            include_summer: self.include_summer,
+            starting_semester: self.starting_semester,
+            starting_year: self.starting_year,
+            max_courses_per_term: self.max_courses_per_term,
+            max_credits_per_term: self.max_credits_per_term,
+            min_credits_per_term: self.min_credits_per_term,
+            max_terms: self.max_terms,
+        }
+        .config()
+    }
+
+    /// When terms start and end, using the usual dates for seasons not given
+    pub fn calendar(&self) -> Result<TermCalendar, SequenceError> {
+        self.calendar
+            .as_deref()
+            .map_or(Ok(TermCalendar::default()), TermCalendar::parse)
+    }
+}
+
+/// The body of a requirements query, with the catalog uploaded as a CSV file
+#[derive(FromForm)]
+pub struct RequirementsRequestBody<'f> {
+    #[field(name = "completed")]
+    #[field(name = "lenient")]
+    #[field(name = "columns")]
+    pub catalog: Flattened<CatalogForm>,
+    pub courses: TempFile<'f>,
+}
+
+impl RequirementsRequestBody<'_> {}
+
+/// The body of a graph request, with the catalog uploaded as a CSV file. The
+/// sequencing options are only needed to cluster courses by term.
+#[derive(FromForm)]
+pub struct GraphRequestBody<'f> {
+    pub include_summer: bool,
+    pub starting_semester: Option<SeasonForm>,
+    pub starting_year: Option<u32>,
+    pub max_courses_per_term: Option<u32>,
+    pub max_credits_per_term: Option<f32>,
+    pub min_credits_per_term: Option<f32>,
+    pub max_terms: Option<u32>,
+    #[field(name = "completed")]
+    #[field(name = "lenient")]
+    #[field(name = "columns")]
+    pub catalog: Flattened<CatalogForm>,
+    pub courses: TempFile<'f>,
+}
+
+impl GraphRequestBody<'_> {
+    /// The config to sequence the courses with when clustering them by term
+    pub fn config(&self) -> Result<SequenceConfig, SequenceError> {
+        let (Some(starting_semester), Some(starting_year)) =
+            (self.starting_semester, self.starting_year)
+        else {
+            return Err(SequenceError::InvalidConfig {
+                message: "a starting semester and year are needed to cluster by term".to_string(),
+            });
+        };
+
+        SequenceOptions {
+            include_summer: self.include_summer,
+            starting_semester,
+            starting_year,
+            max_courses_per_term: self.max_courses_per_term,
+            max_credits_per_term: self.max_credits_per_term,
+            min_credits_per_term: self.min_credits_per_term,
+            max_terms: self.max_terms,
+        }
+        .config()
+    }
+}
+
+/// The fields of a form request saying how to read its uploaded catalog
+#[derive(FromForm)]
+pub struct CatalogForm {
+    /// Codes of courses already taken, including transfer credits, e.g. "MAT 1341, CSI 1100"
+    pub completed: Option<String>,
+    /// Ignore prerequisites that can not be parsed rather than rejecting the file
+    pub lenient: bool,
+    /// Headers to read as course fields, e.g. "Course Number=Catalog; Title=Name"
+    pub columns: Option<String>,
+}
+
+impl CatalogForm {
+    /// How to read the uploaded courses
+    pub fn csv_options(&self) -> Result<CsvOptions, SequenceError> {
+        Ok(CsvOptions {
+            lenient: self.lenient,
+            columns: self
+                .columns
+                .as_deref()
+                .map_or(Ok(ColumnMapping::default()), ColumnMapping::parse)?,
+        })
+    }
+
+    /// The courses the student has already completed
+    pub fn completed_courses(&self) -> Result<Vec<CompletedCourse>, SequenceError> {
+        self.completed
+            .as_deref()
+            .map_or(Ok(vec![]), parse_completed_courses)
+    }
+}
+
+/// A form field reading the fields of `T` from the top level of the form, so
+/// that several forms can share them. The field must be named after each of
+/// the fields of `T`.
+pub struct Flattened<T>(pub T);
+
+#[rocket::async_trait]
+impl<'r, T: FromForm<'r>> FromForm<'r> for Flattened<T> {
+    type Context = T::Context;
+
+    fn init(opts: Options) -> Self::Context {
+        T::init(opts)
+    }
+
+    fn push_value(ctxt: &mut Self::Context, field: ValueField<'r>) {
+        T::push_value(
+            ctxt,
+            ValueField {
+                name: NameView::new(field.name.source()),
+                ..field
+            },
+        )
+    }
+
+    async fn push_data(ctxt: &mut Self::Context, field: DataField<'r, '_>) {
+        T::push_data(
+            ctxt,
+            DataField {
+                name: NameView::new(field.name.source()),
+                ..field
+            },
+        )
+        .await
+    }
+
+    fn default(opts: Options) -> Option<Self> {
+        T::default(opts).map(Flattened)
+    }
+
+    fn finalize(ctxt: Self::Context) -> form::Result<'r, Self> {
+        T::finalize(ctxt).map(Flattened)
+    }
+}
+
+impl<T> Deref for Flattened<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+/// The body of a sequencing request sent as JSON, with the courses given as a
+/// JSON catalog
+#[derive(Deserialize)]
+pub struct JsonRequestBody {
+    #[serde(flatten)]
+    pub options: SequenceOptions,
+    /// Codes of courses already taken, including transfer credits
+    #[serde(default)]
+    pub completed: Vec<String>,
+    pub format: Option<OutputFormat>,
+    /// When terms start and end, for calendar exports
+    #[serde(default)]
+    pub calendar: TermCalendar,
+    pub courses: Vec<CourseJson>,
+}
+
+impl JsonRequestBody {
+    /// The courses the student has already completed
+    pub fn completed_courses(&self) -> Result<Vec<CompletedCourse>, SequenceError> {
+        self.completed.iter().map(|code| code.parse()).collect()
+    }
+}
+
+/// The sequencing options shared by form and JSON requests
+#[derive(Deserialize)]
+pub struct SequenceOptions {
+    pub include_summer: bool,
+    pub starting_semester: SeasonForm,
+    pub starting_year: u32,
+    pub max_courses_per_term: Option<u32>,
+    pub max_credits_per_term: Option<f32>,
+    pub min_credits_per_term: Option<f32>,
+    pub max_terms: Option<u32>,
+}
+
+impl SequenceOptions {
+    /// Builds the sequencing config, using the credit limit alone when no
+    /// course limit is given and rejecting credit limits no term can meet
+    pub fn config(&self) -> Result<SequenceConfig, SequenceError> {
+        match (self.max_courses_per_term, self.max_credits_per_term) {
+            (Some(0), _) => {
+                return Err(SequenceError::InvalidConfig {
+                    message: "max courses per term must be greater than 0".to_string(),
+                })
+            }
+            (None, None) => {
+                return Err(SequenceError::InvalidConfig {
+                    message: "either max courses or max credits per term must be given".to_string(),
+                })
+            }
+            _ => {}
+        }
+
+        let invalid = |message: &str| {
+            Err(SequenceError::InvalidConfig {
+                message: message.to_string(),
+            })
+        };
+        if self
+            .max_credits_per_term
+            .is_some_and(|max| max.is_nan() || max <= 0.0)
+        {
+            return invalid("max credits per term must be greater than 0");
+        }
+        if self
+            .min_credits_per_term
+            .is_some_and(|min| min.is_nan() || min < 0.0)
+        {
+            return invalid("min credits per term must be 0 or more");
+        }
+        if let (Some(min), Some(max)) = (self.min_credits_per_term, self.max_credits_per_term) {
+            if min > max {
+                return invalid(
+                    "min credits per term can not be greater than max credits per term",
+                );
+            }
+        }
+
+        Ok(SequenceConfig {
+            include_summer: self.include_summer,
+            starting_semester: self.starting_semester.into(),
+            starting_year: self.starting_year,
+            max_courses_per_term: self.max_courses_per_term,
+            max_credits_per_term: self.max_credits_per_term,
+            min_credits_per_term: self.min_credits_per_term,
+            max_terms: self.max_terms,
+        })
+    }
+}
+
+/// The formats a sequence can be returned in
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default, Deserialize, FromFormField)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    #[default]
+    Json,
+    Csv,
+    Ical,
+    Html,
+}
+
+impl OutputFormat {
+    /// The format to use when the request does not name one, which is an HTML
+    /// report for clients that prefer HTML and JSON otherwise
+    pub fn negotiate(format: Option<OutputFormat>, accept: Option<&Accept>) -> Self {
+        match (format, accept) {
+            (Some(format), _) => format,
+            (None, Some(accept)) if accept.preferred().is_html() => OutputFormat::Html,
+            (None, _) => OutputFormat::Json,
+        }
+    }
+}
+
+/// A generated sequence in the format requested
+#[derive(Responder)]
+pub enum SequenceResponse {
+    Json(Json<Vec<Term>>),
+    Csv((ContentType, String)),
+    Ical((ContentType, String)),
+    Html(RawHtml<String>),
+}
+
+impl SequenceResponse {
+    pub fn new(
+        terms: Vec<Term>,
+        format: OutputFormat,
+        calendar: &TermCalendar,
+    ) -> Result<Self, SequenceError> {
+        Ok(match format {
+            OutputFormat::Json => SequenceResponse::Json(Json(terms)),
+            OutputFormat::Csv => {
+                SequenceResponse::Csv((ContentType::CSV, sequence_to_csv(&terms)?))
+            }
+            OutputFormat::Ical => SequenceResponse::Ical((
+                ContentType::Calendar,
+                sequence_to_ical(&terms, calendar, SystemTime::now())?,
+            )),
+            OutputFormat::Html => SequenceResponse::Html(RawHtml(sequence_to_html(&terms))),
+        })
+    }
+}
+
+/// The languages a prerequisite graph can be exported in
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default, FromFormField)]
+pub enum GraphFormat {
+    #[default]
+    Dot,
+    Mermaid,
+}
+
+impl GraphFormat {
+    pub fn content_type(&self) -> ContentType {
+        match self {
+            GraphFormat::Dot => ContentType::new("text", "vnd.graphviz"),
+            GraphFormat::Mermaid => ContentType::Plain,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone, FromFormField)]
+pub enum ClusteringForm {
+    None,
+    Level,
+    Term,
+}
+
+impl From<ClusteringForm> for Clustering {
+    fn from(clustering: ClusteringForm) -> Self {
+        match clustering {
+            ClusteringForm::None => Clustering::None,
+            ClusteringForm::Level => Clustering::Level,
+            ClusteringForm::Term => Clustering::Term,
+        }
+    }
+}
+
 #[derive(Debug, Hash, PartialEq, Eq, Copy, Clone, Serialize, Deserialize, FromFormField)]
 pub enum SeasonForm {
     Summer,
@@ -35,7 +390,7 @@ impl From<SeasonForm> for Season {
     }
 }
 
-/**
+/*
  * The following code was written by Natalia Maximo, used with permission
  * Source: https://github.com/cryptaliagy/websvc-rs/blob/main/src/models.rs
  */
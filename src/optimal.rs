@@ -0,0 +1,577 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use super::{
+    config::SequenceConfig,
+    course::{CompletedCourse, Course},
+    critical_path::{
+        chain_lengths, course_priorities, prerequisite_indices, priority_key, CoursePriority,
+    },
+    error::SequenceError,
+    input::{split_completed, validate_input},
+    prerequisites::{validate_corequisites, validate_prerequisites},
+    term::{Season, Term},
+    Sequence, Sequencer,
+};
+
+/// Limits on how much searching the optimal sequencer does before settling
+/// for the best plan found so far
+#[derive(Debug, Serialize, Deserialize, Copy, Clone)]
+pub struct SearchBudget {
+    /// The maximum number of search nodes to expand
+    pub max_nodes: Option<u64>,
+
+    /// The maximum amount of time to spend searching
+    pub time_limit: Option<Duration>,
+}
+
+impl Default for SearchBudget {
+    fn default() -> Self {
+        Self {
+            max_nodes: Some(200_000),
+            time_limit: Some(Duration::from_secs(5)),
+        }
+    }
+}
+
+/// A sequencer that searches for the plan with the fewest terms, built from
+/// the config of a [`Sequencer`]
+#[derive(Serialize)]
+pub struct OptimalSequencer {
+    config: SequenceConfig,
+    budget: SearchBudget,
+}
+
+impl OptimalSequencer {
+    pub fn from_config(config: SequenceConfig) -> Self {
+        OptimalSequencer {
+            config,
+            budget: SearchBudget::default(),
+        }
+    }
+
+    /// Replaces the default search budget
+    pub fn with_budget(mut self, budget: SearchBudget) -> Self {
+        self.budget = budget;
+        self
+    }
+}
+
+impl From<Sequencer> for OptimalSequencer {
+    fn from(sequencer: Sequencer) -> Self {
+        Self::from_config(sequencer.config)
+    }
+}
+
+impl Sequence for OptimalSequencer {
+    fn sequence_with_completed(
+        &self,
+        courses: Vec<Course>,
+        completed: &[CompletedCourse],
+    ) -> Result<Vec<Term>, SequenceError> {
+        let (remaining, taken) = split_completed(courses.clone(), completed);
+        validate_input(&remaining, &taken, &self.config)?;
+
+        let mut search = Search::new(&remaining, taken, &self.config, &self.budget);
+        search.run(self.config.starting_semester, self.config.starting_year);
+
+        // Without a plan from the search, the greedy sequencer either provides one
+        // or explains why there is none
+        let Some(plan) = search.best else {
+            return Sequencer {
+                config: self.config,
+            }
+            .sequence_with_completed(courses, completed);
+        };
+
+        Ok(plan
+            .into_iter()
+            .map(|(season, year, term)| {
+                Term::new(
+                    season,
+                    year,
+                    term.into_iter().map(|i| remaining[i].clone()).collect(),
+                )
+            })
+            .collect())
+    }
+}
+
+/// A term of a partial plan, stored as indices into the course list
+type PlannedTerm = (Season, u32, Vec<usize>);
+
+/// Depth-first branch-and-bound over the courses placed in each term
+struct Search<'a> {
+    courses: &'a [Course],
+    config: &'a SequenceConfig,
+    budget: &'a SearchBudget,
+
+    /// The longest chain of required courses starting at each course
+    chain_lengths: Vec<usize>,
+
+    /// The order in which courses are tried within a term
+    priorities: Vec<CoursePriority>,
+
+    taken: Vec<bool>,
+    courses_taken: Vec<Course>,
+    plan: Vec<PlannedTerm>,
+    best: Option<Vec<PlannedTerm>>,
+
+    /// The earliest term index each (courses taken, season) state was reached at,
+    /// along with the empty terms leading up to it
+    visited: HashMap<(Vec<bool>, Season), (usize, usize)>,
+    nodes: u64,
+    started: Instant,
+}
+
+impl<'a> Search<'a> {
+    fn new(
+        courses: &'a [Course],
+        completed: Vec<Course>,
+        config: &'a SequenceConfig,
+        budget: &'a SearchBudget,
+    ) -> Self {
+        Self {
+            courses,
+            config,
+            budget,
+            chain_lengths: chain_lengths(&prerequisite_indices(courses, true)),
+            priorities: course_priorities(courses),
+            taken: vec![false; courses.len()],
+            courses_taken: completed,
+            plan: vec![],
+            best: None,
+            visited: HashMap::new(),
+            nodes: 0,
+            started: Instant::now(),
+        }
+    }
+
+    fn run(&mut self, season: Season, year: u32) {
+        self.expand(season, year, self.courses.len(), 0);
+    }
+
+    fn budget_exhausted(&self) -> bool {
+        self.budget.max_nodes.is_some_and(|max| self.nodes >= max)
+            || self
+                .budget
+                .time_limit
+                .is_some_and(|limit| self.started.elapsed() >= limit)
+    }
+
+    /// The fewest additional terms any completion of the current plan can take
+    fn lower_bound(&self, remaining: usize) -> usize {
+        let by_load = self
+            .config
+            .max_courses_per_term
+            .map_or(0, |max| remaining.div_ceil(max.max(1) as usize));
+        let by_credits = self.config.max_credits_per_term.map_or(0, |max| {
+            let credits: f32 = (0..self.courses.len())
+                .filter(|&i| !self.taken[i])
+                .map(|i| self.courses[i].credits)
+                .sum();
+            (credits / max).ceil() as usize
+        });
+        let by_chain = (0..self.courses.len())
+            .filter(|&i| !self.taken[i])
+            .map(|i| self.chain_lengths[i])
+            .max()
+            .unwrap_or(0);
+
+        by_load.max(by_credits).max(by_chain)
+    }
+
+    fn expand(&mut self, season: Season, year: u32, remaining: usize, empty_terms: usize) {
+        if remaining == 0 {
+            if self
+                .best
+                .as_ref()
+                .is_none_or(|best| self.plan.len() < best.len())
+            {
+                self.best = Some(self.plan.clone());
+            }
+            return;
+        }
+
+        if self.budget_exhausted() {
+            return;
+        }
+        self.nodes += 1;
+
+        // Only plans shorter than the best so far and within the term limit are of interest
+        let limit = match (&self.best, self.config.max_terms) {
+            (Some(best), _) => Some(best.len()),
+            (None, Some(max_terms)) => Some(max_terms as usize + 1),
+            (None, None) => None,
+        };
+        if limit.is_some_and(|limit| self.plan.len() + self.lower_bound(remaining) >= limit) {
+            return;
+        }
+
+        // A state is only settled by an earlier visit that had at least as many
+        // terms left before the search gives up on empty terms
+        let state = (self.taken.clone(), season);
+        match self.visited.get(&state) {
+            Some(&(depth, empty)) if depth <= self.plan.len() && empty <= empty_terms => return,
+            _ => {
+                self.visited.insert(state, (self.plan.len(), empty_terms));
+            }
+        }
+
+        let mut eligible: Vec<usize> = (0..self.courses.len())
+            .filter(|&i| {
+                !self.taken[i]
+                    && *self.courses[i].terms_offered.get(&season).unwrap_or(&false)
+                    && validate_prerequisites(&self.courses[i].prerequisites, &self.courses_taken)
+            })
+            .collect();
+
+        if eligible.is_empty() || !self.config.allows_courses(1) {
+            // Nothing can change if every season has been tried without progress
+            let seasons_per_year = if self.config.include_summer { 3 } else { 2 };
+            if empty_terms + 1 >= seasons_per_year {
+                return;
+            }
+
+            // Plans running past the last year are left to the greedy sequencer to report
+            let Some((next_season, next_year)) = self.next_term(season, year) else {
+                return;
+            };
+            self.plan.push((season, year, vec![]));
+            self.expand(next_season, next_year, remaining, empty_terms + 1);
+            self.plan.pop();
+            return;
+        }
+
+        // Try courses on the critical path first
+        eligible.sort_by_key(|&i| priority_key(&self.priorities[i], self.courses[i].catalog_code));
+
+        let mut term = vec![];
+        self.choose_term(
+            &eligible,
+            0,
+            &mut term,
+            (season, year),
+            remaining,
+            empty_terms,
+        );
+    }
+
+    /// Tries every term made of the eligible courses that is filled as much as
+    /// the load limits allow, since taking an eligible course earlier never
+    /// delays any other course. Under a minimum load that no longer holds, as a
+    /// full term can leave too little for a later term to reach the minimum, so
+    /// every term is tried, including leaving it empty.
+    fn choose_term(
+        &mut self,
+        eligible: &[usize],
+        next: usize,
+        term: &mut Vec<usize>,
+        (season, year): (Season, u32),
+        remaining: usize,
+        empty_terms: usize,
+    ) {
+        if self.budget_exhausted() {
+            return;
+        }
+
+        if next == eligible.len() {
+            self.nodes += 1;
+
+            if !term.iter().all(|&i| self.corequisites_met(i, term)) {
+                return;
+            }
+
+            let acceptable = match self.config.min_credits_per_term {
+                Some(min) if term.is_empty() => {
+                    let seasons_per_year = if self.config.include_summer { 3 } else { 2 };
+                    min > 0.0 && empty_terms + 1 < seasons_per_year
+                }
+                Some(min) => term.len() == remaining || self.term_credits(term) >= min,
+                None => !eligible.iter().any(|&i| {
+                    !term.contains(&i) && self.fits(term, i) && self.corequisites_met(i, term)
+                }),
+            };
+            if !acceptable {
+                return;
+            }
+
+            for &i in term.iter() {
+                self.taken[i] = true;
+                self.courses_taken.push(self.courses[i].clone());
+            }
+            self.plan.push((season, year, term.clone()));
+
+            let empty_terms = if term.is_empty() { empty_terms + 1 } else { 0 };
+            match self.next_term(season, year) {
+                Some((next_season, next_year)) => {
+                    self.expand(next_season, next_year, remaining - term.len(), empty_terms)
+                }
+                // A finished plan needs no later term
+                None if term.len() == remaining => self.expand(season, year, 0, empty_terms),
+                None => {}
+            }
+
+            self.plan.pop();
+            self.courses_taken
+                .truncate(self.courses_taken.len() - term.len());
+            for &i in term.iter() {
+                self.taken[i] = false;
+            }
+            return;
+        }
+
+        let course = eligible[next];
+        if self.fits(term, course) {
+            term.push(course);
+            self.choose_term(
+                eligible,
+                next + 1,
+                term,
+                (season, year),
+                remaining,
+                empty_terms,
+            );
+            term.pop();
+        }
+
+        // When only full terms are kept, only the course count limits a term and
+        // every course can be placed on its own, skipping a course only works if
+        // there are enough courses left to fill the term
+        let can_still_fill = self.config.min_credits_per_term.is_some()
+            || self.config.max_credits_per_term.is_some()
+            || eligible
+                .iter()
+                .any(|&i| self.courses[i].corequisites.is_some())
+            || term.len() + (eligible.len() - next - 1)
+                >= self
+                    .config
+                    .max_courses_per_term
+                    .map_or(eligible.len(), |max| eligible.len().min(max as usize));
+        if can_still_fill {
+            self.choose_term(
+                eligible,
+                next + 1,
+                term,
+                (season, year),
+                remaining,
+                empty_terms,
+            );
+        }
+    }
+
+    /// Whether a course can be added to a term without exceeding the load limits
+    /// or clashing with an antirequisite
+    fn fits(&self, term: &[usize], course: usize) -> bool {
+        let conflicts = self
+            .courses_taken
+            .iter()
+            .chain(term.iter().map(|&i| &self.courses[i]))
+            .any(|other| self.courses[course].conflicts_with(other));
+
+        !conflicts
+            && self.config.allows_courses(term.len() + 1)
+            && self
+                .config
+                .max_credits_per_term
+                .is_none_or(|max| self.term_credits(term) + self.courses[course].credits <= max)
+    }
+
+    /// Whether a course's corequisites are met by the courses taken so far and
+    /// the courses in its term
+    fn corequisites_met(&self, course: usize, term: &[usize]) -> bool {
+        let term_courses: Vec<Course> = term.iter().map(|&i| self.courses[i].clone()).collect();
+
+        validate_corequisites(
+            &self.courses[course].corequisites,
+            &self.courses_taken,
+            &term_courses,
+        )
+    }
+
+    fn term_credits(&self, term: &[usize]) -> f32 {
+        term.iter().map(|&i| self.courses[i].credits).sum()
+    }
+
+    /// The term after the given one, if its year can be represented
+    fn next_term(&self, season: Season, year: u32) -> Option<(Season, u32)> {
+        let next_season = season.next(self.config.include_summer);
+        if next_season == Season::Winter {
+            Some((next_season, year.checked_add(1)?))
+        } else {
+            Some((next_season, year))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{course::Course, prerequisite_tree::PrerequisiteTree, term::Season};
+
+    use super::*;
+
+    #[test]
+    fn finds_shorter_plan_than_greedy() {
+        let fall_only = Course::test("CSI", 1002)
+            .with_prerequisites(PrerequisiteTree::course("CSI", 1000))
+            .offered_in(&[Season::Fall]);
+        let winter_only = Course::test("CSI", 1001)
+            .with_prerequisites(PrerequisiteTree::course("CSI", 1000))
+            .offered_in(&[Season::Winter]);
+        let winter_elective = Course::test("CSI", 1004).offered_in(&[Season::Winter]);
+
+        let courses = vec![
+            Course::test("CSI", 1000),
+            winter_only,
+            fall_only,
+            Course::test("CSI", 1003).with_prerequisites(PrerequisiteTree::course("CSI", 1000)),
+            winter_elective,
+        ];
+
+        let greedy = Sequencer::new(false, Season::Fall, 2023, 2)
+            .sequence(courses.clone())
+            .unwrap();
+        let optimal = OptimalSequencer::from(Sequencer::new(false, Season::Fall, 2023, 2))
+            .sequence(courses)
+            .unwrap();
+
+        assert_eq!(greedy.len(), 4);
+        assert_eq!(optimal.len(), 3);
+    }
+
+    #[test]
+    fn falls_back_to_greedy_plan_when_budget_is_exhausted() {
+        let courses = vec![
+            Course::test("CSI", 1000),
+            Course::test("CSI", 1001),
+            Course::test("CSI", 1002),
+            Course::test("CSI", 2000).with_prerequisites(PrerequisiteTree::course("CSI", 1002)),
+        ];
+
+        let result = OptimalSequencer::from(Sequencer::new(false, Season::Fall, 2023, 2))
+            .with_budget(SearchBudget {
+                max_nodes: Some(0),
+                time_limit: None,
+            })
+            .sequence(courses)
+            .unwrap();
+
+        assert_eq!(result.iter().map(|t| t.courses.len()).sum::<usize>(), 4);
+    }
+
+    #[test]
+    fn errors_when_no_sequence_exists() {
+        let result =
+            OptimalSequencer::from(Sequencer::new(false, Season::Fall, 2023, 2)).sequence(vec![
+                Course::test("CSI", 1000).with_prerequisites(PrerequisiteTree::course("CSI", 1001)),
+                Course::test("CSI", 1001).with_prerequisites(PrerequisiteTree::course("CSI", 1000)),
+            ]);
+
+        assert!(matches!(
+            result.unwrap_err(),
+            SequenceError::PrerequisiteCycle { .. }
+        ));
+    }
+
+    #[test]
+    fn errors_instead_of_running_past_the_last_year() {
+        let sequencer = OptimalSequencer::from(Sequencer::new(false, Season::Fall, u32::MAX, 2));
+
+        assert!(matches!(
+            sequencer.sequence(vec![
+                Course::test("CSI", 1000),
+                Course::test("CSI", 1001).with_prerequisites(PrerequisiteTree::course("CSI", 1000)),
+            ]),
+            Err(SequenceError::InvalidConfig { .. })
+        ));
+        assert!(sequencer.sequence(vec![Course::test("CSI", 1000)]).is_ok());
+    }
+
+    #[test]
+    fn finds_plan_within_max_terms_greedy_misses() {
+        let fall_only = Course::test("CSI", 1002)
+            .with_prerequisites(PrerequisiteTree::course("CSI", 1000))
+            .offered_in(&[Season::Fall]);
+        let winter_only = Course::test("CSI", 1001)
+            .with_prerequisites(PrerequisiteTree::course("CSI", 1000))
+            .offered_in(&[Season::Winter]);
+        let winter_elective = Course::test("CSI", 1004).offered_in(&[Season::Winter]);
+
+        let courses = vec![
+            Course::test("CSI", 1000),
+            winter_only,
+            fall_only,
+            Course::test("CSI", 1003).with_prerequisites(PrerequisiteTree::course("CSI", 1000)),
+            winter_elective,
+        ];
+
+        let greedy = Sequencer::new(false, Season::Fall, 2023, 2)
+            .with_max_terms(3)
+            .sequence(courses.clone());
+        let optimal =
+            OptimalSequencer::from(Sequencer::new(false, Season::Fall, 2023, 2).with_max_terms(3))
+                .sequence(courses);
+
+        assert!(matches!(
+            greedy,
+            Err(SequenceError::MaxTermsExceeded { max_terms: 3, .. })
+        ));
+        assert_eq!(optimal.unwrap().len(), 3);
+    }
+
+    #[test]
+    fn respects_credit_limits() {
+        let capstone = Course::test("CSI", 4900).with_credits(6.0);
+        let courses = vec![
+            capstone,
+            Course::test("CSI", 1000),
+            Course::test("CSI", 1001),
+            Course::test("CSI", 1002),
+        ];
+
+        let sequencer = Sequencer::new(false, Season::Fall, 2023, 1)
+            .without_course_limit()
+            .with_max_credits_per_term(9.0)
+            .with_min_credits_per_term(6.0);
+        let result = OptimalSequencer::from(sequencer).sequence(courses).unwrap();
+
+        assert_eq!(result.len(), 2);
+        assert!(result
+            .iter()
+            .all(|term| term.credits() <= 9.0 && term.credits() >= 6.0));
+    }
+
+    #[test]
+    fn leaves_terms_unfilled_to_reach_minimum_credits_later() {
+        let winter_only = Course::test("CSI", 1002).offered_in(&[Season::Winter]);
+        let fall_only = Course::test("CSI", 2000)
+            .with_prerequisites(PrerequisiteTree::course("CSI", 1002))
+            .offered_in(&[Season::Fall]);
+        let courses = vec![
+            Course::test("CSI", 1000),
+            Course::test("CSI", 1001),
+            winter_only,
+            fall_only,
+        ];
+
+        // Filling the first fall leaves the winter-only course alone in the
+        // winter, short of the minimum with a course still to follow
+        let greedy = Sequencer::new(false, Season::Fall, 2023, 2)
+            .with_min_credits_per_term(6.0)
+            .sequence(courses.clone());
+        let optimal = OptimalSequencer::from(
+            Sequencer::new(false, Season::Fall, 2023, 2).with_min_credits_per_term(6.0),
+        )
+        .sequence(courses)
+        .unwrap();
+
+        assert!(matches!(
+            greedy,
+            Err(SequenceError::BelowMinimumCredits { .. })
+        ));
+        assert_eq!(optimal.len(), 3);
+        assert!(optimal[0].courses.is_empty());
+        assert!(optimal[1..].iter().all(|term| term.credits() == 6.0));
+    }
+}
@@ -1,11 +1,25 @@
-#[derive(Debug, PartialEq, Clone)]
-pub(crate) struct CourseNode {
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+/// A single course that has to be taken
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct CourseNode {
     pub subject_code: String,
     pub catalog_code: u32,
 }
 
-#[derive(Debug, PartialEq, Clone)]
-pub(crate) struct LogicNode {
+impl CourseNode {
+    pub fn new(subject_code: &str, catalog_code: u32) -> Self {
+        Self {
+            subject_code: subject_code.to_string(),
+            catalog_code,
+        }
+    }
+}
+
+/// The two sides of an AND or OR requirement
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct LogicNode {
     pub left: Box<PrerequisiteTree>,
     pub right: Box<PrerequisiteTree>,
 }
@@ -19,21 +33,121 @@ impl LogicNode {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
-pub(crate) struct MinCreditNode {
+/// A minimum number of units, optionally limited to some subjects and levels
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct MinCreditNode {
     pub credits: u32,
     pub required_subjects: Option<Vec<String>>,
+    /// Levels as the first catalog code of the level, e.g. 3000
     pub required_levels: Option<Vec<u32>>,
 }
 
-#[derive(Debug, PartialEq, Clone)]
-pub(crate) enum PrerequisiteTree {
+/// A parsed prerequisite expression, serialized with a `type` tag naming the
+/// kind of node, e.g. `{"type": "course", "subject_code": "MAT", "catalog_code": 1341}`
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(tag = "type")]
+pub enum PrerequisiteTree {
+    #[serde(rename = "course")]
     CourseNode(CourseNode),
+    #[serde(rename = "or")]
     OrNode(LogicNode),
+    #[serde(rename = "and")]
     AndNode(LogicNode),
+    #[serde(rename = "min_credits")]
     MinCreditNode(MinCreditNode),
 }
 
+impl PrerequisiteTree {
+    /// The text joining the two sides of a logic node
+    fn operator(&self) -> Option<&'static str> {
+        match self {
+            PrerequisiteTree::AndNode(_) => Some(", "),
+            PrerequisiteTree::OrNode(_) => Some(" or "),
+            _ => None,
+        }
+    }
+
+    /// Writes the tree without the closing period, parenthesising the right side
+    /// of a logic node, which the grammar requires, and a left side joined
+    /// differently, for readability
+    fn write_expr(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PrerequisiteTree::CourseNode(node) => write!(f, "{}", node),
+            PrerequisiteTree::MinCreditNode(node) => write!(f, "{}", node),
+            PrerequisiteTree::AndNode(node) | PrerequisiteTree::OrNode(node) => {
+                let operator = self.operator();
+                node.left.write_side(
+                    f,
+                    node.left.operator().is_some_and(|op| Some(op) != operator),
+                )?;
+
+                // Unit requirements read as a clause of their own
+                match (&*node.right, self) {
+                    (PrerequisiteTree::MinCreditNode(_), PrerequisiteTree::AndNode(_)) => {
+                        f.write_str(" and ")?
+                    }
+                    _ => f.write_str(operator.unwrap_or_default())?,
+                }
+                node.right.write_side(f, node.right.operator().is_some())
+            }
+        }
+    }
+
+    fn write_side(&self, f: &mut fmt::Formatter<'_>, parenthesise: bool) -> fmt::Result {
+        if parenthesise {
+            f.write_str("(")?;
+            self.write_expr(f)?;
+            f.write_str(")")
+        } else {
+            self.write_expr(f)
+        }
+    }
+}
+
+/// Renders the canonical prerequisite text. It parses back to the same tree for
+/// every shape the parser produces: courses joined by AND and OR, on their own
+/// or followed by one unit requirement that names its subjects and asks for
+/// fewer than 100 units. Unit requirements nested anywhere else, without
+/// subjects or of 100 units or more are rendered but do not parse.
+impl fmt::Display for PrerequisiteTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_expr(f)?;
+        f.write_str(".")
+    }
+}
+
+impl fmt::Display for CourseNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.subject_code, self.catalog_code)
+    }
+}
+
+impl fmt::Display for MinCreditNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} university units", self.credits)?;
+
+        if let Some(subjects) = &self.required_subjects {
+            write!(f, " in {}", subjects.join(" or "))?;
+        }
+
+        if let Some(levels) = &self.required_levels {
+            let levels: Vec<String> = levels.iter().map(u32::to_string).collect();
+            write!(f, " at the {} level", levels.join(" or "))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+impl PrerequisiteTree {
+    /// A requirement of a single course
+    pub(crate) fn course(subject_code: &str, catalog_code: u32) -> Self {
+        PrerequisiteTree::CourseNode(CourseNode::new(subject_code, catalog_code))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
@@ -201,4 +315,126 @@ mod tests {
 
         assert_eq!(PrerequisiteParser::new().parse(&input).unwrap(), expected);
     }
+
+    #[test]
+    fn display_round_trip_test() {
+        let cases = [
+            ("ITI 1120 or GNG1106.", "ITI 1120 or GNG 1106."),
+            (
+                "MAT1341, (MAT2371 or MAT 2377).",
+                "MAT 1341, (MAT 2371 or MAT 2377).",
+            ),
+            (
+                "MAT 1341, ((MAT 2371, MAT 2375) or MAT 2377).",
+                "MAT 1341, ((MAT 2371, MAT 2375) or MAT 2377).",
+            ),
+            (
+                "MAT 1341 or MAT 1322, CSI 2110.",
+                "(MAT 1341 or MAT 1322), CSI 2110.",
+            ),
+            (
+                "MAT 1341, MAT 1322 and CSI 2110.",
+                "MAT 1341, MAT 1322, CSI 2110.",
+            ),
+            (
+                "CSI 1111 and 18 course units in CSI or SEG at the 3000 level.",
+                "CSI 1111 and 18 university units in CSI or SEG at the 3000 level.",
+            ),
+            (
+                "CSI 1111 or CSI 1112 or 18 university units in CSI.",
+                "CSI 1111 or CSI 1112 or 18 university units in CSI.",
+            ),
+            (
+                "18 course units in Computer Science (CSI) at the 3000 or 4000 level.",
+                "18 university units in CSI at the 3000 or 4000 level.",
+            ),
+        ];
+
+        for (input, canonical) in cases {
+            let tree = PrerequisiteParser::new().parse(input).unwrap();
+
+            assert_eq!(tree.to_string(), canonical);
+            assert_eq!(PrerequisiteParser::new().parse(canonical).unwrap(), tree);
+        }
+    }
+
+    /// Every tree of courses joined by AND and OR up to `depth` levels deep
+    fn course_trees(depth: u32) -> Vec<PrerequisiteTree> {
+        let mut trees = vec![
+            PrerequisiteTree::course("CSI", 1100),
+            PrerequisiteTree::course("MAT", 1341),
+        ];
+        if depth > 0 {
+            let operands = course_trees(depth - 1);
+            for left in &operands {
+                for right in &operands {
+                    let node = LogicNode::new(left.clone(), right.clone());
+                    trees.push(PrerequisiteTree::AndNode(node.clone()));
+                    trees.push(PrerequisiteTree::OrNode(node));
+                }
+            }
+        }
+        trees
+    }
+
+    #[test]
+    fn display_round_trips_every_parseable_shape() {
+        let units = [
+            MinCreditNode {
+                credits: 6,
+                required_subjects: Some(vec!["CSI".to_string()]),
+                required_levels: None,
+            },
+            MinCreditNode {
+                credits: 99,
+                required_subjects: Some(vec!["CSI".to_string(), "SEG".to_string()]),
+                required_levels: Some(vec![3000, 4000]),
+            },
+        ];
+
+        let mut trees = course_trees(2);
+        for expr in course_trees(2) {
+            for node in &units {
+                let unit = PrerequisiteTree::MinCreditNode(node.clone());
+                let logic = LogicNode::new(expr.clone(), unit);
+                trees.push(PrerequisiteTree::AndNode(logic.clone()));
+                trees.push(PrerequisiteTree::OrNode(logic));
+            }
+        }
+        trees.extend(units.into_iter().map(PrerequisiteTree::MinCreditNode));
+
+        let parser = PrerequisiteParser::new();
+        for tree in trees {
+            let text = tree.to_string();
+
+            assert_eq!(parser.parse(&text).ok(), Some(tree), "{}", text);
+        }
+    }
+
+    #[test]
+    fn json_round_trip_test() {
+        let tree = PrerequisiteParser::new()
+            .parse("MAT 1341 or 6 university units in MAT at the 2000 level.")
+            .unwrap();
+
+        let value = serde_json::to_value(&tree).unwrap();
+
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "type": "or",
+                "left": {"type": "course", "subject_code": "MAT", "catalog_code": 1341},
+                "right": {
+                    "type": "min_credits",
+                    "credits": 6,
+                    "required_subjects": ["MAT"],
+                    "required_levels": [2000],
+                },
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<PrerequisiteTree>(value).unwrap(),
+            tree
+        );
+    }
 }
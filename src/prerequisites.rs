@@ -1,8 +1,108 @@
+use serde::Serialize;
+
 use super::{course::Course, prerequisite_tree::PrerequisiteTree};
 
+/// A part of a prerequisite that the courses taken do not meet
+#[derive(Debug, Serialize, PartialEq, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum UnmetRequirement {
+    /// A course that has not been taken
+    Course {
+        subject_code: String,
+        catalog_code: u32,
+    },
+
+    /// Alternatives none of which are met, where meeting every requirement of
+    /// any one alternative is enough
+    AnyOf {
+        alternatives: Vec<Vec<UnmetRequirement>>,
+    },
+
+    /// Units still needed in courses of the given subjects and levels
+    Credits {
+        needed: f32,
+        required: u32,
+        subjects: Option<Vec<String>>,
+        levels: Option<Vec<u32>>,
+    },
+}
+
+/// Explains why the prerequisites of a course are not met by the courses taken,
+/// returning every requirement still outstanding; an empty list means the course
+/// can be taken
+pub fn explain_prerequisites(course: &Course, courses_taken: &[Course]) -> Vec<UnmetRequirement> {
+    match &course.prerequisites {
+        Some(prerequisites) => unmet_requirements(prerequisites, courses_taken),
+        None => vec![],
+    }
+}
+
+pub(crate) fn unmet_requirements(
+    tree: &PrerequisiteTree,
+    courses_taken: &[Course],
+) -> Vec<UnmetRequirement> {
+    match tree {
+        PrerequisiteTree::CourseNode(course_node) => {
+            if evaluate_prerequisite_tree(tree, courses_taken) {
+                vec![]
+            } else {
+                vec![UnmetRequirement::Course {
+                    subject_code: course_node.subject_code.clone(),
+                    catalog_code: course_node.catalog_code,
+                }]
+            }
+        }
+        PrerequisiteTree::AndNode(logic_node) => {
+            let mut unmet = unmet_requirements(&logic_node.left, courses_taken);
+            unmet.extend(unmet_requirements(&logic_node.right, courses_taken));
+            unmet
+        }
+        PrerequisiteTree::OrNode(logic_node) => {
+            let left = unmet_requirements(&logic_node.left, courses_taken);
+            let right = unmet_requirements(&logic_node.right, courses_taken);
+            if left.is_empty() || right.is_empty() {
+                return vec![];
+            }
+
+            // Chained alternatives are listed together rather than nested
+            let mut alternatives = vec![];
+            for side in [left, right] {
+                match <[UnmetRequirement; 1]>::try_from(side) {
+                    Ok(
+                        [UnmetRequirement::AnyOf {
+                            alternatives: nested,
+                        }],
+                    ) => alternatives.extend(nested),
+                    Ok([single]) => alternatives.push(vec![single]),
+                    Err(side) => alternatives.push(side),
+                }
+            }
+            vec![UnmetRequirement::AnyOf { alternatives }]
+        }
+        PrerequisiteTree::MinCreditNode(min_credit_node) => {
+            let earned = credits_in(
+                &min_credit_node.required_levels,
+                &min_credit_node.required_subjects,
+                courses_taken,
+            );
+            let required = min_credit_node.credits;
+            if earned >= required as f32 {
+                vec![]
+            } else {
+                vec![UnmetRequirement::Credits {
+                    needed: required as f32 - earned,
+                    required,
+                    subjects: min_credit_node.required_subjects.clone(),
+                    levels: min_credit_node.required_levels.clone(),
+                }]
+            }
+        }
+    }
+}
+
 pub(crate) fn validate_prerequisites(
     prerequisites: &Option<PrerequisiteTree>,
-    courses_taken: &Vec<Course>,
+    courses_taken: &[Course],
 ) -> bool {
     match prerequisites {
         Some(prerequisites) => evaluate_prerequisite_tree(prerequisites, courses_taken),
@@ -10,12 +110,33 @@ pub(crate) fn validate_prerequisites(
     }
 }
 
-fn evaluate_prerequisite_tree(tree: &PrerequisiteTree, courses_taken: &Vec<Course>) -> bool {
+/// Corequisites are met by courses taken in an earlier term or alongside the course
+pub(crate) fn validate_corequisites(
+    corequisites: &Option<PrerequisiteTree>,
+    courses_taken: &[Course],
+    current_term: &[Course],
+) -> bool {
+    match corequisites {
+        Some(corequisites) => {
+            let available: Vec<Course> = courses_taken
+                .iter()
+                .chain(current_term.iter())
+                .cloned()
+                .collect();
+            evaluate_prerequisite_tree(corequisites, &available)
+        }
+        None => true,
+    }
+}
+
+pub(crate) fn evaluate_prerequisite_tree(
+    tree: &PrerequisiteTree,
+    courses_taken: &[Course],
+) -> bool {
     match tree {
-        PrerequisiteTree::CourseNode(course_node) => courses_taken.iter().any(|course| {
-            course.subject_code == course_node.subject_code
-                && course.catalog_code == course_node.catalog_code
-        }),
+        PrerequisiteTree::CourseNode(course_node) => courses_taken
+            .iter()
+            .any(|course| course.satisfies(course_node)),
         PrerequisiteTree::AndNode(logic_node) => {
             evaluate_prerequisite_tree(&logic_node.left, courses_taken)
                 && evaluate_prerequisite_tree(&logic_node.right, courses_taken)
@@ -37,24 +158,31 @@ fn satisfies_min_credits(
     credits_required: u32,
     required_levels: &Option<Vec<u32>>,
     required_subjects: &Option<Vec<String>>,
-    courses_taken: &Vec<Course>,
+    courses_taken: &[Course],
 ) -> bool {
-    let total_credits = courses_taken
+    credits_in(required_levels, required_subjects, courses_taken) >= credits_required as f32
+}
+
+/// The units of the courses taken that count towards a minimum credit requirement
+fn credits_in(
+    required_levels: &Option<Vec<u32>>,
+    required_subjects: &Option<Vec<String>>,
+    courses_taken: &[Course],
+) -> f32 {
+    courses_taken
         .iter()
         .filter(|course| {
             let course_level = ((course.catalog_code / 1000) % 10) * 1000;
             let subject_matches = required_subjects
                 .as_ref()
-                .map_or(true, |subjects| subjects.contains(&course.subject_code));
+                .is_none_or(|subjects| subjects.contains(&course.subject_code));
             let level_matches = required_levels
                 .as_ref()
-                .map_or(true, |levels| levels.contains(&course_level));
+                .is_none_or(|levels| levels.contains(&course_level));
             subject_matches && level_matches
         })
-        .count()
-        * 3;
-
-    total_credits >= credits_required as usize
+        .map(|course| course.credits)
+        .sum::<f32>()
 }
 
 #[cfg(test)]
@@ -62,9 +190,10 @@ mod tests {
 
     use crate::{
         course::Course,
-        prerequisite_tree::{CourseNode, PrerequisiteTree},
+        prerequisite_tree::{CourseNode, LogicNode, MinCreditNode, PrerequisiteTree},
         prerequisites::{
-            evaluate_prerequisite_tree, satisfies_min_credits, validate_prerequisites,
+            evaluate_prerequisite_tree, explain_prerequisites, satisfies_min_credits,
+            validate_prerequisites, UnmetRequirement,
         },
     };
 
@@ -77,6 +206,11 @@ mod tests {
             name: String::from("A math course"),
             catalog_code: 2132,
             prerequisites: None,
+            corequisites: None,
+            antirequisites: vec![],
+            equivalents: vec![],
+            unparsed_prerequisites: vec![],
+            credits: 3.0,
             terms_offered: HashMap::new(),
         };
         let binding2 = Course {
@@ -84,6 +218,11 @@ mod tests {
             name: String::from("a computing course"),
             catalog_code: 3110,
             prerequisites: None,
+            corequisites: None,
+            antirequisites: vec![],
+            equivalents: vec![],
+            unparsed_prerequisites: vec![],
+            credits: 3.0,
             terms_offered: HashMap::new(),
         };
         let courses_taken = vec![binding, binding2];
@@ -114,6 +253,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_satisfies_min_credits_with_course_weights() {
+        let lab = Course {
+            subject_code: String::from("CSI"),
+            name: String::from("A lab"),
+            catalog_code: 3111,
+            prerequisites: None,
+            corequisites: None,
+            antirequisites: vec![],
+            equivalents: vec![],
+            unparsed_prerequisites: vec![],
+            credits: 1.5,
+            terms_offered: HashMap::new(),
+        };
+        let capstone = Course {
+            subject_code: String::from("CSI"),
+            name: String::from("A capstone project"),
+            catalog_code: 4900,
+            prerequisites: None,
+            corequisites: None,
+            antirequisites: vec![],
+            equivalents: vec![],
+            unparsed_prerequisites: vec![],
+            credits: 6.0,
+            terms_offered: HashMap::new(),
+        };
+        let courses_taken = vec![lab, capstone];
+
+        assert!(
+            satisfies_min_credits(7, &None, &None, &courses_taken),
+            "Should sum the credits of each course taken"
+        );
+
+        assert!(
+            !satisfies_min_credits(3, &Some(vec![3000]), &None, &courses_taken),
+            "Should return false when the matching courses are worth fewer credits"
+        );
+    }
+
     #[test]
     fn test_evaluate_prerequisite_tree() {
         let binding = Course {
@@ -121,6 +299,11 @@ mod tests {
             name: String::from("A computing course"),
             catalog_code: 3110,
             prerequisites: None,
+            corequisites: None,
+            antirequisites: vec![],
+            equivalents: vec![],
+            unparsed_prerequisites: vec![],
+            credits: 3.0,
             terms_offered: HashMap::new(),
         };
 
@@ -129,6 +312,11 @@ mod tests {
             name: String::from("A math course"),
             catalog_code: 2132,
             prerequisites: None,
+            corequisites: None,
+            antirequisites: vec![],
+            equivalents: vec![],
+            unparsed_prerequisites: vec![],
+            credits: 3.0,
             terms_offered: HashMap::new(),
         };
 
@@ -152,6 +340,11 @@ mod tests {
             name: String::from("Advanced computing"),
             catalog_code: 3110,
             prerequisites: None,
+            corequisites: None,
+            antirequisites: vec![],
+            equivalents: vec![],
+            unparsed_prerequisites: vec![],
+            credits: 3.0,
             terms_offered: HashMap::new(),
         };
 
@@ -160,6 +353,11 @@ mod tests {
             name: String::from("Intro to Math"),
             catalog_code: 2132,
             prerequisites: None,
+            corequisites: None,
+            antirequisites: vec![],
+            equivalents: vec![],
+            unparsed_prerequisites: vec![],
+            credits: 3.0,
             terms_offered: HashMap::new(),
         };
 
@@ -175,4 +373,104 @@ mod tests {
             "Should return true when prerequisites are satisfied"
         );
     }
+
+    #[test]
+    fn test_evaluate_prerequisite_tree_with_equivalents() {
+        let mat = |catalog_code| CourseNode {
+            subject_code: String::from("MAT"),
+            catalog_code,
+        };
+        let course = |antirequisites, equivalents| Course {
+            subject_code: String::from("MAT"),
+            name: String::from("Calculus for engineers"),
+            catalog_code: 1320,
+            prerequisites: None,
+            corequisites: None,
+            antirequisites,
+            equivalents,
+            unparsed_prerequisites: vec![],
+            credits: 3.0,
+            terms_offered: HashMap::new(),
+        };
+        let tree = PrerequisiteTree::CourseNode(mat(1330));
+
+        assert!(
+            !evaluate_prerequisite_tree(&tree, &[course(vec![mat(1330)], vec![])]),
+            "Should return false when the course taken is only an antirequisite"
+        );
+        assert!(
+            evaluate_prerequisite_tree(&tree, &[course(vec![], vec![mat(1330)])]),
+            "Should return true when the course taken is an equivalent"
+        );
+    }
+
+    #[test]
+    fn test_explain_prerequisites() {
+        let course = |subject_code: &str, catalog_code, prerequisites| Course {
+            subject_code: String::from(subject_code),
+            name: String::from("A course"),
+            catalog_code,
+            prerequisites,
+            corequisites: None,
+            antirequisites: vec![],
+            equivalents: vec![],
+            unparsed_prerequisites: vec![],
+            credits: 3.0,
+            terms_offered: HashMap::new(),
+        };
+        let node = |subject_code: &str, catalog_code| {
+            PrerequisiteTree::CourseNode(CourseNode {
+                subject_code: String::from(subject_code),
+                catalog_code,
+            })
+        };
+
+        // CSI 2110 and (MAT 1341 or MAT 1322 or 6 units in MAT at the 2000 level)
+        let prerequisites = PrerequisiteTree::AndNode(LogicNode::new(
+            node("CSI", 2110),
+            PrerequisiteTree::OrNode(LogicNode::new(
+                PrerequisiteTree::OrNode(LogicNode::new(node("MAT", 1341), node("MAT", 1322))),
+                PrerequisiteTree::MinCreditNode(MinCreditNode {
+                    credits: 6,
+                    required_subjects: Some(vec![String::from("MAT")]),
+                    required_levels: Some(vec![2000]),
+                }),
+            )),
+        ));
+        let target = course("CSI", 3105, Some(prerequisites));
+        let mut courses_taken = vec![course("MAT", 2384, None)];
+
+        assert_eq!(
+            explain_prerequisites(&target, &courses_taken),
+            vec![
+                UnmetRequirement::Course {
+                    subject_code: String::from("CSI"),
+                    catalog_code: 2110,
+                },
+                UnmetRequirement::AnyOf {
+                    alternatives: vec![
+                        vec![UnmetRequirement::Course {
+                            subject_code: String::from("MAT"),
+                            catalog_code: 1341,
+                        }],
+                        vec![UnmetRequirement::Course {
+                            subject_code: String::from("MAT"),
+                            catalog_code: 1322,
+                        }],
+                        vec![UnmetRequirement::Credits {
+                            needed: 3.0,
+                            required: 6,
+                            subjects: Some(vec![String::from("MAT")]),
+                            levels: Some(vec![2000]),
+                        }],
+                    ],
+                },
+            ]
+        );
+
+        courses_taken.push(course("CSI", 2110, None));
+        courses_taken.push(course("MAT", 1322, None));
+
+        assert!(explain_prerequisites(&target, &courses_taken).is_empty());
+    }
 }
@@ -0,0 +1,301 @@
+use std::ops::Range;
+
+use serde::Deserialize;
+use toml::Spanned;
+
+use super::{
+    config::SequenceConfig,
+    course::{Course, CourseJson, RequisiteInput},
+    error::{PrerequisiteParseError, SequenceError},
+    prerequisite_tree::CourseNode,
+    term::Season,
+};
+
+/// The courses of a program along with the config to sequence them with
+#[derive(Debug)]
+pub struct Program {
+    pub courses: Vec<Course>,
+
+    /// The config given in the file, if any
+    pub config: Option<SequenceConfig>,
+}
+
+// A model mapping a program definition file
+#[derive(Deserialize)]
+struct ProgramFile {
+    #[serde(default)]
+    config: Option<SequenceConfig>,
+    #[serde(default)]
+    courses: Vec<Spanned<ProgramCourse>>,
+}
+
+// A model mapping the courses of a program, which take the fields of a JSON
+// catalog entry but keep where their requisites were written
+#[derive(Deserialize)]
+struct ProgramCourse {
+    subject_code: String,
+    catalog_code: u32,
+    #[serde(alias = "course_name")]
+    name: String,
+    #[serde(default)]
+    prerequisites: Option<Spanned<RequisiteInput>>,
+    #[serde(default)]
+    corequisites: Option<Spanned<RequisiteInput>>,
+    #[serde(default)]
+    antirequisites: Vec<CourseNode>,
+    #[serde(default)]
+    equivalents: Vec<CourseNode>,
+    #[serde(default)]
+    credits: Option<f32>,
+    terms_offered: Vec<Season>,
+}
+
+/// Reads a program definition written in TOML, of the form
+///
+/// ```toml
+/// [config]
+/// include_summer = false
+/// starting_semester = "Fall"
+/// starting_year = 2024
+/// max_courses_per_term = 5
+///
+/// [[courses]]
+/// subject_code = "CSI"
+/// catalog_code = 2110
+/// name = "Data Structures and Algorithms"
+/// credits = 3.0
+/// prerequisites = "CSI 1101 or CSI 1501, ITI 1121."
+/// terms_offered = ["Fall", "Winter"]
+/// ```
+///
+/// Courses take the same fields as the entries of a JSON catalog, and the
+/// config the fields of `SequenceConfig`. Errors give the line they were found on,
+/// and requisites that can not be parsed the column the parser stopped at.
+pub fn parse_program(input: &str) -> Result<Program, SequenceError> {
+    let file: ProgramFile = toml::from_str(input).map_err(|err| {
+        let position = err.span().map(|span| position(input, span.start));
+        SequenceError::InvalidProgram {
+            line: position.map(|(line, _)| line),
+            column: position.map(|(_, column)| column),
+            message: err.message().to_string(),
+        }
+    })?;
+
+    let courses = file
+        .courses
+        .into_iter()
+        .map(|entry| {
+            let (line, _) = position(input, entry.span().start);
+            let course = entry.into_inner();
+            let prerequisites = course.prerequisites.as_ref().map(Spanned::span);
+            let corequisites = course.corequisites.as_ref().map(Spanned::span);
+            let entry = CourseJson::from(course);
+
+            Course::from_json(entry.clone()).map_err(|err| {
+                let found = match &err {
+                    SequenceError::PrerequisiteParse(parse) => {
+                        // Prerequisites are parsed first, so the corequisites only failed
+                        // if the course reads without them
+                        let without_corequisites = CourseJson {
+                            corequisites: None,
+                            ..entry
+                        };
+                        let span = if Course::from_json(without_corequisites).is_err() {
+                            prerequisites
+                        } else {
+                            corequisites
+                        };
+                        span.map(|span| requisite_position(input, span, parse))
+                    }
+                    _ => None,
+                };
+                SequenceError::InvalidProgram {
+                    line: Some(found.map_or(line, |(line, _)| line)),
+                    column: found.map(|(_, column)| column),
+                    message: err.to_string(),
+                }
+            })
+        })
+        .collect::<Result<Vec<Course>, SequenceError>>()?;
+
+    Ok(Program {
+        courses,
+        config: file.config,
+    })
+}
+
+impl From<ProgramCourse> for CourseJson {
+    fn from(course: ProgramCourse) -> Self {
+        CourseJson {
+            subject_code: course.subject_code,
+            catalog_code: course.catalog_code,
+            name: course.name,
+            prerequisites: course.prerequisites.map(Spanned::into_inner),
+            corequisites: course.corequisites.map(Spanned::into_inner),
+            antirequisites: course.antirequisites,
+            equivalents: course.equivalents,
+            credits: course.credits,
+            terms_offered: course.terms_offered,
+        }
+    }
+}
+
+/// The 1-based line and column at which requisites written at `span` failed to parse
+fn requisite_position(
+    input: &str,
+    span: Range<usize>,
+    err: &PrerequisiteParseError,
+) -> (usize, usize) {
+    // The parser's offset only maps onto the file when the text is written as is
+    // between one pair of quotes
+    let written = input.get(span.start + 1..span.end.saturating_sub(1));
+    let offset = match err.offset {
+        Some(offset) if written == Some(err.text.as_str()) => span.start + 1 + offset,
+        _ => span.start,
+    };
+
+    position(input, offset)
+}
+
+/// The 1-based line and column of a byte offset
+fn position(input: &str, offset: usize) -> (usize, usize) {
+    let before = &input[..offset.min(input.len())];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_program_test() {
+        let toml = r#"
+[config]
+include_summer = false
+starting_semester = "Fall"
+starting_year = 2024
+max_courses_per_term = 5
+max_credits_per_term = 15.0
+
+[[courses]]
+subject_code = "CSI"
+catalog_code = 1101
+name = "Introduction to Computing"
+terms_offered = ["Fall", "Winter"]
+
+[[courses]]
+subject_code = "CSI"
+catalog_code = 2110
+name = "Data Structures and Algorithms"
+credits = 1.5
+prerequisites = "CSI 1101."
+terms_offered = ["Winter"]
+"#;
+
+        let program = parse_program(toml).unwrap();
+        let config = program.config.unwrap();
+
+        assert_eq!(config.starting_semester, Season::Fall);
+        assert_eq!(config.max_courses_per_term, Some(5));
+        assert_eq!(config.max_credits_per_term, Some(15.0));
+        assert_eq!(config.min_credits_per_term, None);
+        assert_eq!(program.courses.len(), 2);
+        assert_eq!(program.courses[1].credits, 1.5);
+        assert!(program.courses[1].prerequisites.is_some());
+        assert!(!program.courses[1].terms_offered[&Season::Fall]);
+    }
+
+    #[test]
+    fn parse_program_without_course_limit_test() {
+        let toml = r#"
+[config]
+include_summer = true
+starting_semester = "Winter"
+starting_year = 2025
+max_credits_per_term = 12.0
+"#;
+
+        let config = parse_program(toml).unwrap().config.unwrap();
+
+        assert_eq!(config.max_courses_per_term, None);
+        assert_eq!(config.max_credits_per_term, Some(12.0));
+    }
+
+    #[test]
+    fn parse_program_errors_test() {
+        let toml = "[[courses]]\nsubject_code = \"CSI\"\ncatalog_code = \"twenty\"\n";
+
+        assert!(matches!(
+            parse_program(toml),
+            Err(SequenceError::InvalidProgram { line: Some(3), .. })
+        ));
+
+        let toml = r#"[[courses]]
+subject_code = "CSI"
+catalog_code = 1101
+name = "Introduction to Computing"
+terms_offered = ["Fall"]
+
+[[courses]]
+subject_code = "CSI"
+catalog_code = 2110
+name = "Data Structures and Algorithms"
+prerequisites = "CSI 1101; ITI 1121."
+terms_offered = ["Winter"]
+"#;
+
+        match parse_program(toml) {
+            Err(SequenceError::InvalidProgram {
+                line,
+                column,
+                message,
+            }) => {
+                assert_eq!((line, column), (Some(11), Some(26)));
+                assert!(message.contains("CSI 2110"));
+            }
+            other => panic!("expected an invalid program error, got {:?}", other),
+        }
+
+        let toml = r#"[[courses]]
+subject_code = "CSI"
+catalog_code = 2110
+name = "Data Structures and Algorithms"
+prerequisites = "CSI 1101."
+corequisites = """
+MAT 1348 or"""
+terms_offered = ["Winter"]
+"#;
+
+        assert!(matches!(
+            parse_program(toml),
+            Err(SequenceError::InvalidProgram {
+                line: Some(6),
+                column: Some(16),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn parse_program_errors_at_the_requisites_that_failed_test() {
+        // Corequisite sections read as prerequisites but not as corequisites
+        let toml = r#"[[courses]]
+subject_code = "CSI"
+catalog_code = 2110
+name = "Data Structures and Algorithms"
+prerequisites = "CSI 1101. Corequisite: MAT 1348."
+corequisites = "CSI 1101. Corequisite: MAT 1348."
+terms_offered = ["Winter"]
+"#;
+
+        assert!(matches!(
+            parse_program(toml),
+            Err(SequenceError::InvalidProgram { line: Some(6), .. })
+        ));
+    }
+}
@@ -0,0 +1,307 @@
+use super::{
+    course::Course,
+    term::{Season, Term},
+};
+
+/// A readable summary of a sequence, with a table per academic year, the
+/// credits of each term, the requisites of each course and any warnings
+pub struct Report<'a> {
+    terms: &'a [Term],
+    warnings: Vec<String>,
+}
+
+/// The terms of one academic year, which runs from fall to summer
+struct AcademicYear<'a> {
+    start: u32,
+    terms: Vec<&'a Term>,
+}
+
+impl<'a> Report<'a> {
+    /// Creates a report of `terms`, warning about empty terms and
+    /// prerequisites that could not be read
+    pub fn new(terms: &'a [Term]) -> Self {
+        let mut warnings = vec![];
+
+        for term in terms {
+            if term.courses.is_empty() {
+                warnings.push(format!(
+                    "No courses are taken in {} {}",
+                    term.season, term.year
+                ));
+            }
+            for course in &term.courses {
+                for text in &course.unparsed_prerequisites {
+                    warnings.push(format!(
+                        "The prerequisites of {} could not be read and were ignored: {}",
+                        course_code(course),
+                        text
+                    ));
+                }
+            }
+        }
+
+        Self { terms, warnings }
+    }
+
+    /// Adds a warning to the end of the report
+    pub fn with_warning(mut self, warning: impl Into<String>) -> Self {
+        self.warnings.push(warning.into());
+        self
+    }
+
+    /// Renders the report as Markdown
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("# Course sequence\n");
+
+        for year in self.academic_years() {
+            out += &format!("\n## {}\n\n", year.label());
+            out += &markdown_row(&["Term", "Course", "Name", "Units", "Requisites"]);
+            out += &markdown_row(&["---"; 5]);
+
+            for term in year.terms {
+                let label = term_label(term);
+                if term.courses.is_empty() {
+                    out += &markdown_row(&[&label, "", "No courses", "", ""]);
+                }
+                for (index, course) in term.courses.iter().enumerate() {
+                    out += &markdown_row(&[
+                        if index == 0 { &label } else { "" },
+                        &course_code(course),
+                        &escape_markdown(&course.name),
+                        &course.credits.to_string(),
+                        &escape_markdown(&requisite_notes(course).join(" ")),
+                    ]);
+                }
+            }
+        }
+
+        if !self.warnings.is_empty() {
+            out += "\n## Warnings\n\n";
+            for warning in &self.warnings {
+                out += &format!("- {}\n", escape_markdown(warning));
+            }
+        }
+
+        out
+    }
+
+    /// Renders the report as a self-contained HTML page
+    pub fn to_html(&self) -> String {
+        let mut out = String::from(
+            "<!DOCTYPE html>\n\
+             <html lang=\"en\">\n\
+             <head>\n\
+             <meta charset=\"utf-8\">\n\
+             <title>Course sequence</title>\n\
+             <style>\n\
+             body { font-family: sans-serif; margin: 2em; }\n\
+             table { border-collapse: collapse; margin-bottom: 2em; }\n\
+             th, td { border: 1px solid #ccc; padding: 0.4em 0.8em; text-align: left; vertical-align: top; }\n\
+             th { background: #f0f0f0; }\n\
+             .warnings { color: #a15c00; }\n\
+             </style>\n\
+             </head>\n\
+             <body>\n\
+             <h1>Course sequence</h1>\n",
+        );
+
+        for year in self.academic_years() {
+            out += &format!("<h2>{}</h2>\n<table>\n", year.label());
+            out += "<tr><th>Term</th><th>Course</th><th>Name</th><th>Units</th><th>Requisites</th></tr>\n";
+
+            for term in year.terms {
+                let label = escape_html(&term_label(term));
+                if term.courses.is_empty() {
+                    out += &format!(
+                        "<tr><td>{}</td><td></td><td>No courses</td><td></td><td></td></tr>\n",
+                        label
+                    );
+                }
+                for (index, course) in term.courses.iter().enumerate() {
+                    let term_cell = if index == 0 {
+                        format!("<td rowspan=\"{}\">{}</td>", term.courses.len(), label)
+                    } else {
+                        String::new()
+                    };
+                    let notes: Vec<String> = requisite_notes(course)
+                        .iter()
+                        .map(|note| escape_html(note))
+                        .collect();
+                    out += &format!(
+                        "<tr>{}<td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                        term_cell,
+                        escape_html(&course_code(course)),
+                        escape_html(&course.name),
+                        course.credits,
+                        notes.join("<br>")
+                    );
+                }
+            }
+
+            out += "</table>\n";
+        }
+
+        if !self.warnings.is_empty() {
+            out += "<h2>Warnings</h2>\n<ul class=\"warnings\">\n";
+            for warning in &self.warnings {
+                out += &format!("<li>{}</li>\n", escape_html(warning));
+            }
+            out += "</ul>\n";
+        }
+
+        out += "</body>\n</html>\n";
+        out
+    }
+
+    /// Groups consecutive terms into the academic years they fall in
+    fn academic_years(&self) -> Vec<AcademicYear<'a>> {
+        let mut years: Vec<AcademicYear> = vec![];
+
+        for term in self.terms {
+            let start = match term.season {
+                Season::Fall => term.year,
+                Season::Winter | Season::Summer => term.year - 1,
+            };
+            match years.last_mut() {
+                Some(year) if year.start == start => year.terms.push(term),
+                _ => years.push(AcademicYear {
+                    start,
+                    terms: vec![term],
+                }),
+            }
+        }
+
+        years
+    }
+}
+
+impl AcademicYear<'_> {
+    fn label(&self) -> String {
+        format!("{}-{}", self.start, self.start + 1)
+    }
+}
+
+/// Renders a sequence as a Markdown report
+pub fn sequence_to_markdown(terms: &[Term]) -> String {
+    Report::new(terms).to_markdown()
+}
+
+/// Renders a sequence as a self-contained HTML report
+pub fn sequence_to_html(terms: &[Term]) -> String {
+    Report::new(terms).to_html()
+}
+
+fn course_code(course: &Course) -> String {
+    format!("{} {}", course.subject_code, course.catalog_code)
+}
+
+fn term_label(term: &Term) -> String {
+    format!("{} {} ({} units)", term.season, term.year, term.credits())
+}
+
+/// The prerequisites and corequisites of a course as canonical text
+fn requisite_notes(course: &Course) -> Vec<String> {
+    let mut notes = vec![];
+
+    if let Some(prerequisites) = &course.prerequisites {
+        notes.push(format!("Prerequisites: {}", prerequisites));
+    }
+    if let Some(corequisites) = &course.corequisites {
+        notes.push(format!("Corequisites: {}", corequisites));
+    }
+    for text in &course.unparsed_prerequisites {
+        notes.push(format!("Not checked: {}", text));
+    }
+
+    notes
+}
+
+/// A Markdown table row, with a single space in empty cells
+fn markdown_row(cells: &[&str]) -> String {
+    let mut row = String::from("|");
+    for cell in cells {
+        if cell.is_empty() {
+            row += " |";
+        } else {
+            row += &format!(" {} |", cell);
+        }
+    }
+    row + "\n"
+}
+
+fn escape_markdown(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prerequisite_tree::PrerequisiteTree;
+
+    use super::*;
+
+    fn terms() -> Vec<Term> {
+        let mut unparsed = Course::test("CSI", 3105)
+            .with_name("Design & analysis")
+            .with_prerequisites(PrerequisiteTree::course("CSI", 2110));
+        unparsed.unparsed_prerequisites = vec!["Permission of the department".to_string()];
+
+        vec![
+            Term::new(
+                Season::Fall,
+                2024,
+                vec![Course::test("CSI", 1100).with_name("Programming | part I")],
+            ),
+            Term::new(
+                Season::Winter,
+                2025,
+                vec![Course::test("CSI", 2110)
+                    .with_name("Data structures")
+                    .with_prerequisites(PrerequisiteTree::course("CSI", 1100))],
+            ),
+            Term::new(Season::Fall, 2025, vec![]),
+            Term::new(Season::Winter, 2026, vec![unparsed]),
+        ]
+    }
+
+    #[test]
+    fn sequence_to_markdown_test() {
+        let markdown = sequence_to_markdown(&terms());
+
+        assert!(markdown.contains("## 2024-2025\n"));
+        assert!(markdown.contains("## 2025-2026\n"));
+        assert!(
+            markdown.contains("| Fall 2024 (3 units) | CSI 1100 | Programming \\| part I | 3 | |")
+        );
+        assert!(markdown.contains(
+            "| Winter 2025 (3 units) | CSI 2110 | Data structures | 3 | Prerequisites: CSI 1100. |"
+        ));
+        assert!(markdown.contains("| Fall 2025 (0 units) | | No courses | | |"));
+        assert!(markdown.contains("- No courses are taken in Fall 2025\n"));
+        assert!(markdown.contains(
+            "- The prerequisites of CSI 3105 could not be read and were ignored: Permission of the department\n"
+        ));
+    }
+
+    #[test]
+    fn sequence_to_html_test() {
+        let html = Report::new(&terms())
+            .with_warning("Check the <catalog>")
+            .to_html();
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert_eq!(html.matches("<table>").count(), 2);
+        assert!(html.contains("<td>Design &amp; analysis</td>"));
+        assert!(
+            html.contains("Prerequisites: CSI 2110.<br>Not checked: Permission of the department")
+        );
+        assert!(html.contains("<li>Check the &lt;catalog&gt;</li>"));
+        assert!(html.ends_with("</html>\n"));
+    }
+}
@@ -0,0 +1,579 @@
+use std::collections::BTreeSet;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use super::{
+    course::{Course, CourseInfo},
+    critical_path::prerequisite_references,
+    error::SequenceError,
+    input::{blocked_prerequisites, missing_courses, reaching, shortest_cycle},
+    optimal::SearchBudget,
+    prerequisite_tree::{MinCreditNode, PrerequisiteTree},
+    prerequisites::{evaluate_prerequisite_tree, unmet_requirements, UnmetRequirement},
+};
+
+/// How much searching is done for a cheaper plan than the one first found,
+/// kept small as requirements are looked up on request
+const BUDGET: SearchBudget = SearchBudget {
+    max_nodes: Some(50_000),
+    time_limit: Some(Duration::from_secs(1)),
+};
+
+/// Everything that has to be taken before a course
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Requirements {
+    pub course: CourseInfo,
+
+    /// The courses to take first, each listed after the courses it requires
+    pub courses: Vec<CourseInfo>,
+
+    /// Units required along the way that the completed and listed courses do not cover
+    pub credits: Vec<UnmetRequirement>,
+}
+
+/// The courses chosen to meet a requirement, and the unit requirements met
+/// along the way
+#[derive(Debug, Clone, Default)]
+struct Plan<'a> {
+    courses: BTreeSet<usize>,
+    credits: Vec<&'a MinCreditNode>,
+}
+
+/// Searches for the cheapest set of courses that meets the prerequisites of a
+/// course, making every choice between alternatives against the courses
+/// already chosen so that a course shared by several requirements is only
+/// counted once
+struct Planner<'a> {
+    courses: &'a [Course],
+    completed: &'a [Course],
+
+    /// How many rounds of taking every course that can be taken pass before
+    /// each course can be taken, if it ever can
+    rounds: Vec<Option<usize>>,
+
+    budget: SearchBudget,
+    nodes: u64,
+    started: Instant,
+
+    plan: Plan<'a>,
+    best: Option<Plan<'a>>,
+}
+
+impl<'a> Planner<'a> {
+    fn budget_exhausted(&self) -> bool {
+        self.budget.max_nodes.is_some_and(|max| self.nodes >= max)
+            || self
+                .budget
+                .time_limit
+                .is_some_and(|limit| self.started.elapsed() >= limit)
+    }
+
+    /// Meets every pending requirement, trying each way of meeting those with
+    /// alternatives. `pending` is left as it was given.
+    fn search(&mut self, pending: &mut Vec<&'a PrerequisiteTree>) {
+        if self.budget_exhausted()
+            || self
+                .best
+                .as_ref()
+                .is_some_and(|best| self.cost(&self.plan.courses) >= self.cost(&best.courses))
+        {
+            return;
+        }
+        self.nodes += 1;
+
+        let Some(tree) = pending.pop() else {
+            if self.can_be_ordered() {
+                self.best = Some(self.plan.clone());
+            }
+            return;
+        };
+
+        if evaluate_prerequisite_tree(tree, self.completed) {
+            self.search(pending);
+            pending.push(tree);
+            return;
+        }
+
+        match tree {
+            PrerequisiteTree::CourseNode(node) => {
+                let candidates: Vec<usize> = (0..self.courses.len())
+                    .filter(|&i| self.rounds[i].is_some() && self.courses[i].satisfies(node))
+                    .collect();
+
+                // A course already in the plan costs nothing, so one is enough to try
+                if candidates.iter().any(|i| self.plan.courses.contains(i)) {
+                    self.search(pending);
+                }
+
+                for candidate in candidates {
+                    if self.plan.courses.contains(&candidate) {
+                        continue;
+                    }
+
+                    let before = pending.len();
+                    pending.extend(&self.courses[candidate].prerequisites);
+                    self.plan.courses.insert(candidate);
+                    self.search(pending);
+                    self.plan.courses.remove(&candidate);
+                    pending.truncate(before);
+                }
+            }
+            PrerequisiteTree::AndNode(node) => {
+                pending.push(&node.right);
+                pending.push(&node.left);
+                self.search(pending);
+                pending.truncate(pending.len() - 2);
+            }
+            PrerequisiteTree::OrNode(node) => {
+                for side in [&node.left, &node.right] {
+                    pending.push(side);
+                    self.search(pending);
+                    pending.pop();
+                }
+            }
+            PrerequisiteTree::MinCreditNode(node) => {
+                self.plan.credits.push(node);
+                self.search(pending);
+                self.plan.credits.pop();
+            }
+        }
+
+        pending.push(tree);
+    }
+
+    /// A plan that always orders, found by meeting each requirement with the
+    /// courses that can be taken soonest. Each course chosen can then be taken
+    /// a round earlier than the course requiring it.
+    fn soonest_plan(&self, pending: &mut Vec<&'a PrerequisiteTree>) -> Plan<'a> {
+        let mut plan = self.plan.clone();
+
+        while let Some(tree) = pending.pop() {
+            if evaluate_prerequisite_tree(tree, self.completed) {
+                continue;
+            }
+
+            match tree {
+                PrerequisiteTree::CourseNode(node) => {
+                    let soonest = (0..self.courses.len())
+                        .filter(|&i| self.courses[i].satisfies(node))
+                        .filter_map(|i| Some((self.rounds[i]?, i)))
+                        .min();
+                    if let Some((_, course)) = soonest {
+                        if plan.courses.insert(course) {
+                            pending.extend(&self.courses[course].prerequisites);
+                        }
+                    }
+                }
+                PrerequisiteTree::AndNode(node) => {
+                    pending.push(&node.right);
+                    pending.push(&node.left);
+                }
+                PrerequisiteTree::OrNode(node) => {
+                    let side = [&node.left, &node.right]
+                        .into_iter()
+                        .min_by_key(|side| self.rounds_until_met(side))
+                        .unwrap_or(&node.left);
+                    pending.push(side);
+                }
+                PrerequisiteTree::MinCreditNode(node) => plan.credits.push(node),
+            }
+        }
+
+        plan
+    }
+
+    /// How many rounds pass before the courses a tree requires can all have
+    /// been taken, if they ever can
+    fn rounds_until_met(&self, tree: &PrerequisiteTree) -> Option<usize> {
+        if evaluate_prerequisite_tree(tree, self.completed) {
+            return Some(0);
+        }
+
+        match tree {
+            PrerequisiteTree::CourseNode(node) => (0..self.courses.len())
+                .filter(|&i| self.courses[i].satisfies(node))
+                .filter_map(|i| self.rounds[i])
+                .min()
+                .map(|round| round + 1),
+            PrerequisiteTree::AndNode(node) => {
+                let left = self.rounds_until_met(&node.left)?;
+                let right = self.rounds_until_met(&node.right)?;
+                Some(left.max(right))
+            }
+            PrerequisiteTree::OrNode(node) => [&node.left, &node.right]
+                .into_iter()
+                .filter_map(|side| self.rounds_until_met(side))
+                .min(),
+            PrerequisiteTree::MinCreditNode(_) => Some(0),
+        }
+    }
+
+    /// The units, then the number of courses, a plan takes
+    fn cost(&self, courses: &BTreeSet<usize>) -> (f32, usize) {
+        let credits = courses.iter().map(|&i| self.courses[i].credits).sum();
+        (credits, courses.len())
+    }
+
+    /// Whether the courses of the plan can be taken one after another, which
+    /// fails when choices made elsewhere leave courses requiring each other
+    fn can_be_ordered(&self) -> bool {
+        let chosen: Vec<Course> = self
+            .plan
+            .courses
+            .iter()
+            .map(|&i| self.courses[i].clone())
+            .collect();
+
+        takeable_rounds(&chosen, self.completed)
+            .into_iter()
+            .all(|round| round.is_some())
+    }
+}
+
+/// Finds the courses that have to be taken before a course, ordered so that
+/// every course comes after the courses it requires. Alternatives are chosen so
+/// that the courses taken together are worth the fewest units, settling for the
+/// cheapest plan found when the search runs out of budget. Completed courses
+/// meet prerequisites and count towards unit requirements along with the
+/// courses listed.
+pub fn requirements(
+    subject_code: &str,
+    catalog_code: u32,
+    courses: &[Course],
+    completed: &[Course],
+) -> Result<Requirements, SequenceError> {
+    let is_target = |course: &&Course| {
+        course.subject_code == subject_code && course.catalog_code == catalog_code
+    };
+
+    if let Some(course) = completed.iter().find(is_target) {
+        return Ok(Requirements {
+            course: course.info(),
+            courses: vec![],
+            credits: vec![],
+        });
+    }
+
+    let target = courses
+        .iter()
+        .position(|course| is_target(&course))
+        .ok_or_else(|| SequenceError::UnknownCourse {
+            code: format!("{} {}", subject_code, catalog_code),
+        })?;
+
+    let rounds = takeable_rounds(courses, completed);
+    if rounds[target].is_none() {
+        let reachable: Vec<bool> = rounds.iter().map(Option::is_some).collect();
+        return Err(unsatisfiable(target, courses, completed, &reachable));
+    }
+
+    let mut planner = Planner {
+        courses,
+        completed,
+        rounds,
+        budget: BUDGET,
+        nodes: 0,
+        started: Instant::now(),
+        plan: Plan {
+            courses: BTreeSet::from([target]),
+            credits: vec![],
+        },
+        best: None,
+    };
+
+    // The search only keeps plans cheaper than one known to order, so it can
+    // stop whenever its budget runs out
+    let prerequisites: Vec<&PrerequisiteTree> = courses[target].prerequisites.iter().collect();
+    planner.best = Some(planner.soonest_plan(&mut prerequisites.clone()));
+    planner.search(&mut prerequisites.clone());
+
+    let mut plan = planner.best.unwrap_or_default();
+    plan.courses.remove(&target);
+
+    let order = topological_order(&plan.courses, courses);
+    let taken: Vec<Course> = completed
+        .iter()
+        .chain(order.iter().map(|&i| &courses[i]))
+        .cloned()
+        .collect();
+
+    let mut credits = vec![];
+    for node in plan.credits {
+        for unmet in unmet_requirements(&PrerequisiteTree::MinCreditNode(node.clone()), &taken) {
+            if !credits.contains(&unmet) {
+                credits.push(unmet);
+            }
+        }
+    }
+
+    Ok(Requirements {
+        course: courses[target].info(),
+        courses: order.iter().map(|&i| courses[i].info()).collect(),
+        credits,
+    })
+}
+
+/// How many rounds of taking every course that can be taken pass before each
+/// course can be taken, if it ever can, taking unit requirements to be met
+/// since they are reported rather than planned for
+fn takeable_rounds(courses: &[Course], completed: &[Course]) -> Vec<Option<usize>> {
+    let mut rounds = vec![None; courses.len()];
+    let mut available = completed.to_vec();
+    for round in 0.. {
+        let newly_takeable: Vec<usize> = (0..courses.len())
+            .filter(|&i| {
+                rounds[i].is_none()
+                    && courses[i]
+                        .prerequisites
+                        .as_ref()
+                        .is_none_or(|tree| courses_met(tree, &available))
+            })
+            .collect();
+
+        if newly_takeable.is_empty() {
+            break;
+        }
+        for i in newly_takeable {
+            rounds[i] = Some(round);
+            available.push(courses[i].clone());
+        }
+    }
+
+    rounds
+}
+
+/// Whether the courses a tree requires have been taken, ignoring its unit
+/// requirements
+fn courses_met(tree: &PrerequisiteTree, courses_taken: &[Course]) -> bool {
+    match tree {
+        PrerequisiteTree::CourseNode(_) => evaluate_prerequisite_tree(tree, courses_taken),
+        PrerequisiteTree::AndNode(node) => {
+            courses_met(&node.left, courses_taken) && courses_met(&node.right, courses_taken)
+        }
+        PrerequisiteTree::OrNode(node) => {
+            courses_met(&node.left, courses_taken) || courses_met(&node.right, courses_taken)
+        }
+        PrerequisiteTree::MinCreditNode(_) => true,
+    }
+}
+
+/// Explains why a course can never be taken: the courses its prerequisites
+/// reference that are not in the catalog or, when they all are, a cycle of
+/// courses requiring each other that it depends on
+fn unsatisfiable(
+    target: usize,
+    courses: &[Course],
+    completed: &[Course],
+    reachable: &[bool],
+) -> SequenceError {
+    let available: Vec<Course> = completed.iter().chain(courses).cloned().collect();
+    let blocked_by = blocked_prerequisites(courses, reachable);
+    let mut held_back = reaching(target, &blocked_by);
+    if !held_back.contains(&target) {
+        held_back.insert(0, target);
+    }
+
+    let mut missing: Vec<String> = held_back
+        .iter()
+        .filter_map(|&i| courses[i].prerequisites.as_ref())
+        .flat_map(|tree| missing_courses(tree, &available))
+        .collect();
+    missing.sort();
+    missing.dedup();
+
+    if missing.is_empty() {
+        if let Some(cycle) = held_back
+            .iter()
+            .find_map(|&i| shortest_cycle(i, &blocked_by))
+        {
+            return SequenceError::PrerequisiteCycle {
+                cycle: cycle.iter().map(|&i| courses[i].info()).collect(),
+            };
+        }
+    }
+
+    SequenceError::UnsatisfiablePrerequisite {
+        course: courses[target].info(),
+        missing,
+    }
+}
+
+/// Orders the chosen courses so that each comes after the chosen courses it
+/// references, taking lower catalog codes first when there is a choice
+fn topological_order(chosen: &BTreeSet<usize>, courses: &[Course]) -> Vec<usize> {
+    let references = prerequisite_references(courses);
+    let mut remaining: Vec<usize> = chosen.iter().copied().collect();
+    remaining.sort_by(|&a, &b| {
+        (courses[a].catalog_code, &courses[a].subject_code)
+            .cmp(&(courses[b].catalog_code, &courses[b].subject_code))
+    });
+
+    let mut ordered = vec![];
+    while !remaining.is_empty() {
+        // Courses that reference each other are taken in catalog order
+        let next = remaining
+            .iter()
+            .position(|&i| {
+                references[i]
+                    .iter()
+                    .all(|j| *j == i || !remaining.contains(j))
+            })
+            .unwrap_or(0);
+        ordered.push(remaining.remove(next));
+    }
+
+    ordered
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::csv::parse_csv_to_courses;
+
+    use super::*;
+
+    const CATALOG: &str = "Subject,Catalog,Name,Prerequisites,Credits,Terms\n\
+                   MAT,1341,Linear algebra,,3,F/W\n\
+                   MAT,1348,Discrete math,,3,F/W\n\
+                   MAT,2377,Probability,MAT 1341.,3,F/W\n\
+                   ITI,1100,Digital systems,,3,F/W\n\
+                   CSI,1100,Programming I,,3,F/W\n\
+                   CSI,1200,Programming II,CSI 1100.,3,F/W\n\
+                   CSI,2110,Data structures,\"CSI 1200, (MAT 2377 or MAT 1348).\",3,F/W\n\
+                   CSI,3105,Algorithms,\"CSI 2110, (MAT 1348 or ITI 1100).\",3,F/W\n\
+                   CSI,4900,Project,\"CSI 3105, 6 university units in CSI at the 4000 level.\",3,F/W";
+
+    fn courses() -> Vec<Course> {
+        parse_csv_to_courses(CATALOG).unwrap()
+    }
+
+    fn codes(requirements: &Requirements) -> Vec<String> {
+        requirements
+            .courses
+            .iter()
+            .map(|course| format!("{} {}", course.subject_code, course.catalog_code))
+            .collect()
+    }
+
+    #[test]
+    fn chooses_cheapest_alternatives_in_order() {
+        let result = requirements("CSI", 3105, &courses(), &[]).unwrap();
+
+        // MAT 1348 is an alternative for both CSI 2110 and CSI 3105, so taking
+        // it once is cheaper than MAT 2377, which needs MAT 1341, or ITI 1100
+        assert_eq!(
+            codes(&result),
+            vec!["CSI 1100", "CSI 1200", "MAT 1348", "CSI 2110"]
+        );
+        assert!(result.credits.is_empty());
+    }
+
+    #[test]
+    fn chooses_shared_alternatives_whichever_side_they_are_on() {
+        let catalog = CATALOG.replace("(MAT 1348 or ITI 1100)", "(ITI 1100 or MAT 1348)");
+        let courses = parse_csv_to_courses(&catalog).unwrap();
+
+        let result = requirements("CSI", 3105, &courses, &[]).unwrap();
+
+        assert_eq!(
+            codes(&result),
+            vec!["CSI 1100", "CSI 1200", "MAT 1348", "CSI 2110"]
+        );
+    }
+
+    #[test]
+    fn skips_completed_courses_and_reports_missing_units() {
+        let courses = courses();
+        let completed: Vec<Course> = courses
+            .iter()
+            .filter(|course| course.catalog_code == 1100)
+            .cloned()
+            .collect();
+
+        let result = requirements("CSI", 4900, &courses, &completed).unwrap();
+
+        assert_eq!(
+            codes(&result),
+            vec!["CSI 1200", "MAT 1348", "CSI 2110", "CSI 3105"]
+        );
+        assert_eq!(
+            result.credits,
+            vec![UnmetRequirement::Credits {
+                needed: 6.0,
+                required: 6,
+                subjects: Some(vec!["CSI".to_string()]),
+                levels: Some(vec![4000]),
+            }]
+        );
+    }
+
+    #[test]
+    fn errors_for_unknown_or_unsatisfiable_courses() {
+        let mut courses = courses();
+
+        assert_eq!(
+            requirements("CSI", 9999, &courses, &[]).unwrap_err(),
+            SequenceError::UnknownCourse {
+                code: "CSI 9999".to_string()
+            }
+        );
+
+        courses.retain(|course| course.subject_code != "CSI" || course.catalog_code != 1100);
+
+        assert_eq!(
+            requirements("CSI", 3105, &courses, &[]).unwrap_err(),
+            SequenceError::UnsatisfiablePrerequisite {
+                course: courses[6].info(),
+                missing: vec!["CSI 1100".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn settles_for_a_plan_when_alternatives_chain() {
+        // Every course requires either course of the level below it, giving
+        // far more combinations than the search can try
+        let mut catalog = "Subject,Catalog,Name,Prerequisites,Credits,Terms\n\
+                           CSI,1000,Level 0,,3,F/W\n\
+                           CSI,1001,Level 0,,3,F/W\n"
+            .to_string();
+        for level in 1..40 {
+            let below = 1000 + 2 * (level - 1);
+            for catalog_code in [1000 + 2 * level, 1001 + 2 * level] {
+                catalog += &format!(
+                    "CSI,{},Level {},CSI {} or CSI {}.,3,F/W\n",
+                    catalog_code,
+                    level,
+                    below,
+                    below + 1
+                );
+            }
+        }
+        let courses = parse_csv_to_courses(&catalog).unwrap();
+
+        let result = requirements("CSI", 1078, &courses, &[]).unwrap();
+
+        assert_eq!(result.courses.len(), 39);
+    }
+
+    #[test]
+    fn reports_cycles_that_hold_a_course_back() {
+        let catalog = CATALOG.replace(
+            "CSI,1100,Programming I,,",
+            "CSI,1100,Programming I,CSI 2110.,",
+        );
+        let courses = parse_csv_to_courses(&catalog).unwrap();
+
+        assert_eq!(
+            requirements("CSI", 3105, &courses, &[]).unwrap_err(),
+            SequenceError::PrerequisiteCycle {
+                cycle: vec![
+                    courses[4].info(),
+                    courses[6].info(),
+                    courses[5].info(),
+                    courses[4].info(),
+                ],
+            }
+        );
+    }
+}
@@ -1,5 +1,8 @@
 use core::fmt;
-use serde::{Deserialize, Serialize};
+use serde::{
+    ser::{SerializeStruct, Serializer},
+    Deserialize, Serialize,
+};
 
 use super::course::Course;
 
@@ -37,13 +40,13 @@ impl Season {
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug)]
 pub struct Term {
     /// The season during which the term takes place
-    season: Season,
+    pub(crate) season: Season,
 
     /// The year during which the term takes place
-    year: u32,
+    pub(crate) year: u32,
 
     /// The courses taken during the term
     pub courses: Vec<Course>,
@@ -57,12 +60,41 @@ impl Term {
             courses,
         }
     }
+
+    /// The total number of units of the courses in the term
+    pub fn credits(&self) -> f32 {
+        // Summing floats starts from -0.0, which would show an empty term as "-0 units"
+        self.courses
+            .iter()
+            .fold(0.0, |total, course| total + course.credits)
+    }
+}
+
+impl Serialize for Term {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("Term", 4)?;
+        state.serialize_field("season", &self.season)?;
+        state.serialize_field("year", &self.year)?;
+        state.serialize_field("courses", &self.courses)?;
+        state.serialize_field("credits", &self.credits())?;
+
+        state.end()
+    }
 }
 
 impl fmt::Display for Term {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "Term: {} {}", self.season, self.year)?;
-        if &self.courses.len() > &0 {
+        writeln!(
+            f,
+            "Term: {} {} ({} units)",
+            self.season,
+            self.year,
+            self.credits()
+        )?;
+        if !self.courses.is_empty() {
             for course in &self.courses {
                 writeln!(f, "{}", course)?;
             }
@@ -73,3 +105,36 @@ impl fmt::Display for Term {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn serializes_total_credits() {
+        let course = |catalog_code, credits| Course {
+            subject_code: "CSI".to_string(),
+            name: String::from("A computing course"),
+            catalog_code,
+            prerequisites: None,
+            corequisites: None,
+            antirequisites: vec![],
+            equivalents: vec![],
+            unparsed_prerequisites: vec![],
+            credits,
+            terms_offered: HashMap::from([(Season::Fall, true)]),
+        };
+        let term = Term::new(
+            Season::Fall,
+            2023,
+            vec![course(1100, 3.0), course(1101, 1.5)],
+        );
+
+        let value = serde_json::to_value(&term).unwrap();
+
+        assert_eq!(value["credits"], 4.5);
+        assert_eq!(value["courses"][1]["credits"], 1.5);
+    }
+}